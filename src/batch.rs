@@ -0,0 +1,218 @@
+use crate::flatten::bounded_segments;
+use crate::geometry::{
+    approximate_integral, approximate_inverse_integral, map_to_basic_lanes, ParabolaParams,
+};
+use crate::{Point, QuadraticBezier};
+
+///
+/// Many quadratics stored as structure of arrays, so that `flatten` can run the parabola
+/// approximation of `QuadraticBezier::smart_subdivide` on `LANES` curves at once. The lanes
+/// are plain arrays processed by branch-free loops, which the compiler turns into SSE or AVX
+/// instructions depending on the target features, without any `std::arch` code.
+///
+#[derive(Debug, Clone, Default)]
+pub struct QuadraticBatch {
+    x0: Vec<f32>,
+    y0: Vec<f32>,
+    x1: Vec<f32>,
+    y1: Vec<f32>,
+    x2: Vec<f32>,
+    y2: Vec<f32>,
+}
+
+///
+/// Per lane values that only depend on the curve, see `QuadraticBezier::map_to_basic`
+///
+struct LaneParams<const LANES: usize> {
+    a0: [f32; LANES],
+    da: [f32; LANES],
+    u0: [f32; LANES],
+    du: [f32; LANES],
+    n: [f32; LANES],
+    segments: [usize; LANES],
+}
+
+impl QuadraticBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            x0: Vec::with_capacity(capacity),
+            y0: Vec::with_capacity(capacity),
+            x1: Vec::with_capacity(capacity),
+            y1: Vec::with_capacity(capacity),
+            x2: Vec::with_capacity(capacity),
+            y2: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.x0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.x0.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        for column in self.columns_mut() {
+            column.clear();
+        }
+    }
+
+    pub fn push(&mut self, q: QuadraticBezier) {
+        let [p0, p1, p2] = q.points();
+
+        self.x0.push(p0.x);
+        self.y0.push(p0.y);
+        self.x1.push(p1.x);
+        self.y1.push(p1.y);
+        self.x2.push(p2.x);
+        self.y2.push(p2.y);
+    }
+
+    fn columns_mut(&mut self) -> [&mut Vec<f32>; 6] {
+        [
+            &mut self.x0,
+            &mut self.y0,
+            &mut self.x1,
+            &mut self.y1,
+            &mut self.x2,
+            &mut self.y2,
+        ]
+    }
+
+    ///
+    /// Flattens every curve with the parabola approximation and appends the points to `points`.
+    /// For the i-th curve, `offsets` receives the index of its first point, followed by one
+    /// past the last point of the batch, so curve i is `points[offsets[i]..offsets[i + 1]]`
+    /// when both buffers start empty. Reusing the buffers across frames avoids allocating.
+    ///
+    /// `LANES` is the number of curves processed together, 4 or 8 map well to SSE and AVX
+    /// registers. The parameters of the curves and of their points are computed lane-wise,
+    /// only the rounding of the segment counts and the copy to `points` go one curve at a
+    /// time. The points match `smart_subdivide` followed by `eval` up to rounding.
+    ///
+    pub fn flatten<const LANES: usize>(
+        &self,
+        tolerance: f32,
+        points: &mut Vec<Point>,
+        offsets: &mut Vec<usize>,
+    ) {
+        offsets.reserve(self.len() + 1);
+
+        for start in (0..self.len()).step_by(LANES) {
+            let lanes = usize::min(LANES, self.len() - start);
+            let load = |column: &[f32]| {
+                let mut lane = [0.0_f32; LANES];
+                lane[..lanes].copy_from_slice(&column[start..start + lanes]);
+                lane
+            };
+            let (x0, y0, x1, y1, x2, y2) = (
+                load(&self.x0),
+                load(&self.y0),
+                load(&self.x1),
+                load(&self.y1),
+                load(&self.x2),
+                load(&self.y2),
+            );
+            let params = lane_params(tolerance, [&x0, &y0, &x1, &y1, &x2, &y2]);
+
+            // Every curve gets segments + 1 points, the inner ones are filled in below
+            let mut first = [0_usize; LANES];
+            for lane in 0..lanes {
+                first[lane] = points.len();
+                offsets.push(points.len());
+                points.resize(
+                    points.len() + params.segments[lane] + 1,
+                    Point { x: 0.0, y: 0.0 },
+                );
+                points[first[lane]] = Point {
+                    x: x0[lane],
+                    y: y0[lane],
+                };
+                points[first[lane] + params.segments[lane]] = Point {
+                    x: x2[lane],
+                    y: y2[lane],
+                };
+            }
+
+            let max_segments = params.segments[..lanes].iter().copied().max().unwrap_or(0);
+
+            for i in 1..max_segments {
+                let mut t = [0.0_f32; LANES];
+
+                for (lane, t) in t.iter_mut().enumerate() {
+                    let u = approximate_inverse_integral(
+                        params.a0[lane] + (params.da[lane] * i as f32) / params.n[lane],
+                    );
+                    *t = (u - params.u0[lane]) / params.du[lane];
+                }
+
+                let mut x = [0.0_f32; LANES];
+                let mut y = [0.0_f32; LANES];
+
+                for lane in 0..LANES {
+                    let mt = 1.0 - t[lane];
+                    x[lane] = x0[lane] * mt * mt
+                        + 2.0 * x1[lane] * t[lane] * mt
+                        + x2[lane] * t[lane] * t[lane];
+                    y[lane] = y0[lane] * mt * mt
+                        + 2.0 * y1[lane] * t[lane] * mt
+                        + y2[lane] * t[lane] * t[lane];
+                }
+
+                for lane in 0..lanes {
+                    if i < params.segments[lane] {
+                        points[first[lane] + i] = Point {
+                            x: x[lane],
+                            y: y[lane],
+                        };
+                    }
+                }
+            }
+        }
+
+        offsets.push(points.len());
+    }
+}
+
+fn lane_params<const LANES: usize>(
+    tolerance: f32,
+    coordinates: [&[f32; LANES]; 6],
+) -> LaneParams<LANES> {
+    let basic = map_to_basic_lanes(coordinates);
+    let a0 = basic.x0.map(approximate_integral);
+    let a2 = basic.x2.map(approximate_integral);
+    let mut params = LaneParams {
+        a0,
+        da: [0.0; LANES],
+        u0: a0.map(approximate_inverse_integral),
+        du: a2.map(approximate_inverse_integral),
+        n: [0.0; LANES],
+        segments: [0; LANES],
+    };
+
+    for lane in 0..LANES {
+        params.da[lane] = a2[lane] - a0[lane];
+        params.du[lane] -= params.u0[lane];
+    }
+
+    // The rounding to a whole count is the only step left per lane
+    for lane in 0..LANES {
+        let count = ParabolaParams {
+            x0: basic.x0[lane],
+            x2: basic.x2[lane],
+            scale: basic.scale[lane],
+        }
+        .segment_count(a0[lane], a2[lane], tolerance);
+
+        // Straight lines get a single segment and the count is capped, as in `smart_subdivide`
+        params.segments[lane] = bounded_segments(count);
+        params.n[lane] = params.segments[lane] as f32;
+    }
+
+    params
+}
@@ -0,0 +1,273 @@
+use std::time::{Duration, Instant};
+
+use crate::flatten::Flattener;
+use crate::{Point, QuadraticBezier};
+
+///
+/// Curve samples per polyline segment when measuring the actual error
+///
+const SAMPLES_PER_SEGMENT: usize = 16;
+
+///
+/// Statistics of one flattening method at one tolerance over a corpus of curves
+///
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub corpus: String,
+    pub method: String,
+    pub tolerance: f32,
+    pub curves: usize,
+    pub nanos_per_curve: f64,
+    pub segments: usize,
+    ///
+    /// Largest distance from a curve to its polyline, infinite when a method produced
+    /// non-finite points
+    ///
+    pub max_error: f32,
+    ///
+    /// Mean over the curves of the largest distance from each curve to its polyline
+    ///
+    pub mean_error: f32,
+}
+
+impl Measurement {
+    pub fn mean_segments(&self) -> f64 {
+        self.segments as f64 / self.curves.max(1) as f64
+    }
+}
+
+///
+/// Random curves with control points in [0, size)^2, the same seed always gives the same curves
+///
+pub fn random_quadratics(count: usize, seed: u64, size: f32) -> Vec<QuadraticBezier> {
+    let mut state = seed;
+    let mut next = || {
+        // Knuth's MMIX linear congruential generator, the high bits are the good ones
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (state >> 40) as f32 / (1_u64 << 24) as f32 * size
+    };
+
+    (0..count)
+        .map(|_| {
+            let p0 = Point {
+                x: next(),
+                y: next(),
+            };
+            let p1 = Point {
+                x: next(),
+                y: next(),
+            };
+            let p2 = Point {
+                x: next(),
+                y: next(),
+            };
+
+            QuadraticBezier::new(p0, p1, p2)
+        })
+        .collect()
+}
+
+///
+/// Largest distance between the curve and the polyline approximating it, estimated from
+/// samples of the curve. The polyline points are expected in increasing t, as every
+/// `Flattener` produces them, so curve samples and segments are walked together.
+///
+pub fn flattening_error(q: &QuadraticBezier, points: &[Point]) -> f32 {
+    if points.len() < 2 {
+        return f32::INFINITY;
+    }
+
+    let samples = (points.len() - 1) * SAMPLES_PER_SEGMENT;
+    let mut segment = 0;
+    let mut error = 0.0_f32;
+
+    for k in 0..=samples {
+        let p = q.eval(k as f32 / samples as f32);
+
+        while segment + 2 < points.len()
+            && distance_to_segment(p, points[segment + 1], points[segment + 2])
+                <= distance_to_segment(p, points[segment], points[segment + 1])
+        {
+            segment += 1;
+        }
+
+        let distance = distance_to_segment(p, points[segment], points[segment + 1]);
+
+        if distance.is_nan() {
+            return f32::INFINITY;
+        }
+
+        error = error.max(distance);
+    }
+
+    error
+}
+
+fn distance_to_segment(p: Point, a: Point, b: Point) -> f32 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_squared = dx * dx + dy * dy;
+    let t = if length_squared > 0.0 {
+        (((p.x - a.x) * dx + (p.y - a.y) * dy) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+
+    p.distance(a.lerp(b, t))
+}
+
+///
+/// Times `flattener` over `curves`, repeating the whole corpus until `min_duration` has
+/// passed, then measures the segment counts and errors in a separate untimed pass
+///
+pub fn measure(
+    corpus: &str,
+    method: &str,
+    flattener: impl Flattener,
+    curves: &[QuadraticBezier],
+    tolerance: f32,
+    min_duration: Duration,
+) -> Measurement {
+    let start = Instant::now();
+    let mut passes = 0_u32;
+
+    loop {
+        let mut checksum = 0.0_f32;
+
+        for q in curves {
+            for p in q.flatten(tolerance, &flattener) {
+                checksum += p.x;
+            }
+        }
+
+        std::hint::black_box(checksum);
+        passes += 1;
+
+        if start.elapsed() >= min_duration {
+            break;
+        }
+    }
+
+    let elapsed = start.elapsed();
+    let mut points = Vec::<Point>::new();
+    let mut segments = 0;
+    let mut max_error = 0.0_f32;
+    let mut error_sum = 0.0_f64;
+
+    for q in curves {
+        points.clear();
+        points.extend(q.flatten(tolerance, &flattener));
+
+        let error = flattening_error(q, points.as_slice());
+
+        segments += points.len().saturating_sub(1);
+        max_error = max_error.max(error);
+        error_sum += error as f64;
+    }
+
+    Measurement {
+        corpus: corpus.to_string(),
+        method: method.to_string(),
+        tolerance,
+        curves: curves.len(),
+        nanos_per_curve: elapsed.as_nanos() as f64 / (passes as f64 * curves.len().max(1) as f64),
+        segments,
+        max_error,
+        mean_error: (error_sum / curves.len().max(1) as f64) as f32,
+    }
+}
+
+const CSV_HEADER: &str =
+    "corpus,method,tolerance,curves,ns_per_curve,segments,mean_segments,max_error,mean_error";
+
+///
+/// Quotes a field containing a separator, a quote or a line break, doubling its quotes as in
+/// RFC 4180
+///
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn to_csv(measurements: &[Measurement]) -> String {
+    let mut result = String::from(CSV_HEADER);
+    result.push('\n');
+
+    for m in measurements {
+        result.push_str(
+            format!(
+                "{},{},{},{},{:.1},{},{:.3},{},{}\n",
+                csv_field(m.corpus.as_str()),
+                csv_field(m.method.as_str()),
+                m.tolerance,
+                m.curves,
+                m.nanos_per_curve,
+                m.segments,
+                m.mean_segments(),
+                m.max_error,
+                m.mean_error,
+            )
+            .as_str(),
+        );
+    }
+
+    result
+}
+
+///
+/// Quoted JSON string, escaping quotes, backslashes and control characters as in RFC 8259
+///
+fn json_string(value: &str) -> String {
+    let mut result = String::from("\"");
+
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if (c as u32) < 0x20 => result.push_str(format!("\\u{:04x}", c as u32).as_str()),
+            c => result.push(c),
+        }
+    }
+
+    result.push('"');
+    result
+}
+
+pub fn to_json(measurements: &[Measurement]) -> String {
+    // JSON has no infinity, an unbounded error is written as null
+    let number = |value: f32| {
+        if value.is_finite() {
+            value.to_string()
+        } else {
+            "null".to_string()
+        }
+    };
+    let entries = measurements
+        .iter()
+        .map(|m| {
+            format!(
+                "  {{\"corpus\": {}, \"method\": {}, \"tolerance\": {}, \"curves\": {}, \
+                 \"ns_per_curve\": {:.1}, \"segments\": {}, \"mean_segments\": {:.3}, \
+                 \"max_error\": {}, \"mean_error\": {}}}",
+                json_string(m.corpus.as_str()),
+                json_string(m.method.as_str()),
+                m.tolerance,
+                m.curves,
+                m.nanos_per_curve,
+                m.segments,
+                m.mean_segments(),
+                number(m.max_error),
+                number(m.mean_error),
+            )
+        })
+        .collect::<Vec<String>>();
+
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
@@ -0,0 +1,161 @@
+#[cfg(all(feature = "libm", not(any(feature = "std", test))))]
+use crate::math::FloatExt;
+
+///
+/// Porter-Duff compositing operators
+/// See: https://www.w3.org/TR/compositing-1/#porterduffcompositingoperators
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompositeOp {
+    Clear,
+    Src,
+    Dst,
+    #[default]
+    SrcOver,
+    DstOver,
+    SrcIn,
+    DstIn,
+    SrcOut,
+    DstOut,
+    SrcAtop,
+    DstAtop,
+    Xor,
+    Plus,
+}
+
+///
+/// Separable blend modes
+/// See: https://www.w3.org/TR/compositing-1/#blendingseparable
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+}
+
+impl CompositeOp {
+    ///
+    /// Fractions (Fa, Fb) of source and backdrop kept by the operator
+    ///
+    fn factors(&self, src_a: f32, dst_a: f32) -> (f32, f32) {
+        match self {
+            CompositeOp::Clear => (0.0, 0.0),
+            CompositeOp::Src => (1.0, 0.0),
+            CompositeOp::Dst => (0.0, 1.0),
+            CompositeOp::SrcOver => (1.0, 1.0 - src_a),
+            CompositeOp::DstOver => (1.0 - dst_a, 1.0),
+            CompositeOp::SrcIn => (dst_a, 0.0),
+            CompositeOp::DstIn => (0.0, src_a),
+            CompositeOp::SrcOut => (1.0 - dst_a, 0.0),
+            CompositeOp::DstOut => (0.0, 1.0 - src_a),
+            CompositeOp::SrcAtop => (dst_a, 1.0 - src_a),
+            CompositeOp::DstAtop => (1.0 - dst_a, src_a),
+            CompositeOp::Xor => (1.0 - dst_a, 1.0 - src_a),
+            CompositeOp::Plus => (1.0, 1.0),
+        }
+    }
+}
+
+impl BlendMode {
+    ///
+    /// B(cb, cs) on straight color components
+    ///
+    pub fn blend(&self, cb: f32, cs: f32) -> f32 {
+        match self {
+            BlendMode::Normal => cs,
+            BlendMode::Multiply => cb * cs,
+            BlendMode::Screen => cb + cs - cb * cs,
+            BlendMode::Overlay => BlendMode::HardLight.blend(cs, cb),
+            BlendMode::Darken => cb.min(cs),
+            BlendMode::Lighten => cb.max(cs),
+            BlendMode::ColorDodge => {
+                if cb <= 0.0 {
+                    0.0
+                } else if cs >= 1.0 {
+                    1.0
+                } else {
+                    f32::min(1.0, cb / (1.0 - cs))
+                }
+            }
+            BlendMode::ColorBurn => {
+                if cb >= 1.0 {
+                    1.0
+                } else if cs <= 0.0 {
+                    0.0
+                } else {
+                    1.0 - f32::min(1.0, (1.0 - cb) / cs)
+                }
+            }
+            BlendMode::HardLight => {
+                if cs <= 0.5 {
+                    BlendMode::Multiply.blend(cb, 2.0 * cs)
+                } else {
+                    BlendMode::Screen.blend(cb, 2.0 * cs - 1.0)
+                }
+            }
+            BlendMode::SoftLight => {
+                if cs <= 0.5 {
+                    cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+                } else {
+                    let d = if cb <= 0.25 {
+                        ((16.0 * cb - 12.0) * cb + 4.0) * cb
+                    } else {
+                        cb.sqrt()
+                    };
+
+                    cb + (2.0 * cs - 1.0) * (d - cb)
+                }
+            }
+            BlendMode::Difference => (cb - cs).abs(),
+            BlendMode::Exclusion => cb + cs - 2.0 * cb * cs,
+        }
+    }
+}
+
+///
+/// Composites premultiplied RGBA `src` onto premultiplied RGBA `dst`: the source is first
+/// blended with the backdrop using `mode`, then combined with it using `op`.
+///
+pub fn composite(src: [f32; 4], dst: [f32; 4], op: CompositeOp, mode: BlendMode) -> [f32; 4] {
+    let (src_a, dst_a) = (src[3], dst[3]);
+    let mut src_color = [src[0], src[1], src[2]];
+
+    if mode != BlendMode::Normal && src_a > 0.0 && dst_a > 0.0 {
+        for (i, c) in src_color.iter_mut().enumerate() {
+            let cs = src[i] / src_a;
+            let cb = dst[i] / dst_a;
+            let mixed = (1.0 - dst_a) * cs + dst_a * mode.blend(cb, cs);
+
+            *c = mixed * src_a;
+        }
+    }
+
+    let (fa, fb) = op.factors(src_a, dst_a);
+    let channel = |s: f32, d: f32| {
+        let value = s * fa + d * fb;
+
+        if op == CompositeOp::Plus {
+            value.min(1.0)
+        } else {
+            value
+        }
+    };
+
+    [
+        channel(src_color[0], dst[0]),
+        channel(src_color[1], dst[1]),
+        channel(src_color[2], dst[2]),
+        channel(src_a, dst_a),
+    ]
+}
@@ -0,0 +1,188 @@
+use crate::blend::{BlendMode, CompositeOp};
+use crate::color::{linear_to_srgb, srgb_to_linear};
+use crate::paint::Paint;
+use crate::path::Path;
+use crate::rasterizer::{composite_pixel, Blending, FillRule, Rasterizer, SubdivisionMethod};
+use crate::Color;
+
+///
+/// RGBA render target, every filled path is accumulated on its own, resolved
+/// and then composited with the existing content using its own color.
+///
+pub struct Canvas {
+    pub width: usize,
+    pub height: usize,
+    ///
+    /// RGBA with premultiplied alpha, components between 0.0 and 1.0
+    ///
+    pub pixels: Vec<f32>,
+    pub method: SubdivisionMethod,
+    ///
+    /// Used by every following fill, like the blending state of a 2D context
+    ///
+    pub composite_op: CompositeOp,
+    pub blend_mode: BlendMode,
+    ///
+    /// With linear blending the pixels stay sRGB encoded, they are only decoded while compositing
+    ///
+    pub blending: Blending,
+    rasterizer: Rasterizer,
+}
+
+impl Canvas {
+    ///
+    /// Creates a transparent canvas
+    ///
+    pub fn new(width: usize, height: usize, tolerance: f32, method: SubdivisionMethod) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0.0_f32; width * height * 4],
+            method,
+            composite_op: CompositeOp::SrcOver,
+            blend_mode: BlendMode::Normal,
+            blending: Blending::Srgb,
+            rasterizer: Rasterizer {
+                width,
+                height,
+                tolerance,
+                accumulation_buffer: vec![0.0_f32; width * height],
+            },
+        }
+    }
+
+    pub fn tolerance(&self) -> f32 {
+        self.rasterizer.tolerance
+    }
+
+    pub fn clear(&mut self, color: Color) {
+        self.pixels.chunks_mut(4).for_each(|chunk| {
+            chunk[0] = color.r * color.a;
+            chunk[1] = color.g * color.a;
+            chunk[2] = color.b * color.a;
+            chunk[3] = color.a;
+        });
+    }
+
+    ///
+    /// Returns the pixels with straight alpha, ready to be encoded
+    ///
+    pub fn to_rgba(&self) -> Vec<f32> {
+        let mut result = self.pixels.clone();
+
+        result.chunks_mut(4).for_each(|chunk| {
+            if chunk[3] > 0.0 {
+                chunk[0] /= chunk[3];
+                chunk[1] /= chunk[3];
+                chunk[2] /= chunk[3];
+            }
+        });
+
+        result
+    }
+
+    pub fn fill_path(&mut self, path: &Path, paint: &Paint, fill_rule: FillRule) {
+        let method = self.method;
+
+        self.fill_with(paint, fill_rule, |rasterizer| {
+            rasterizer.draw_path(path, method);
+        });
+    }
+
+    ///
+    /// Lets `draw` accumulate any geometry into a cleared rasterizer, then composites it
+    ///
+    pub fn fill_with(
+        &mut self,
+        paint: &Paint,
+        fill_rule: FillRule,
+        draw: impl FnOnce(&mut Rasterizer),
+    ) {
+        self.rasterizer.clear();
+        draw(&mut self.rasterizer);
+        self.composite(paint, fill_rule);
+    }
+
+    ///
+    /// Composites `paint` with the current operator and blend mode, the accumulated coverage
+    /// acts as a clip so pixels outside of the shape are left untouched.
+    ///
+    fn composite(&mut self, paint: &Paint, fill_rule: FillRule) {
+        for y in 0..self.height {
+            let mut acc = 0.0_f32;
+
+            for x in 0..self.width {
+                acc += self.rasterizer.accumulation_buffer[y * self.width + x];
+
+                let coverage = fill_rule.coverage(acc);
+
+                if coverage <= 0.0 {
+                    continue;
+                }
+
+                // Paints are sampled at pixel centers
+                let mut color = paint.color_at(x as f32 + 0.5, y as f32 + 0.5);
+                let i = (y * self.width + x) * 4;
+                let mut dst = [
+                    self.pixels[i],
+                    self.pixels[i + 1],
+                    self.pixels[i + 2],
+                    self.pixels[i + 3],
+                ];
+
+                if let Blending::Linear { .. } = self.blending {
+                    color = color.to_linear();
+                    dst = premultiplied_to_linear(dst);
+                }
+
+                let mut mixed = composite_pixel(
+                    color,
+                    dst,
+                    coverage,
+                    self.blending,
+                    self.composite_op,
+                    self.blend_mode,
+                );
+
+                if let Blending::Linear { .. } = self.blending {
+                    mixed = linear_to_premultiplied(mixed);
+                }
+
+                self.pixels[i..i + 4].copy_from_slice(&mixed);
+            }
+        }
+    }
+}
+
+///
+/// Decodes a premultiplied sRGB pixel to premultiplied linear light
+///
+fn premultiplied_to_linear(pixel: [f32; 4]) -> [f32; 4] {
+    if pixel[3] <= 0.0 {
+        return pixel;
+    }
+
+    let a = pixel[3];
+
+    [
+        srgb_to_linear(pixel[0] / a) * a,
+        srgb_to_linear(pixel[1] / a) * a,
+        srgb_to_linear(pixel[2] / a) * a,
+        a,
+    ]
+}
+
+fn linear_to_premultiplied(pixel: [f32; 4]) -> [f32; 4] {
+    if pixel[3] <= 0.0 {
+        return pixel;
+    }
+
+    let a = pixel[3];
+
+    [
+        linear_to_srgb(pixel[0] / a) * a,
+        linear_to_srgb(pixel[1] / a) * a,
+        linear_to_srgb(pixel[2] / a) * a,
+        a,
+    ]
+}
@@ -1,3 +1,6 @@
+#[cfg(all(feature = "libm", not(any(feature = "std", test))))]
+use crate::math::FloatExt;
+
 ///
 /// Colors in range 0.0-1.0
 ///
@@ -9,6 +12,15 @@ pub struct Color {
     pub a: f32,
 }
 
+///
+/// Foreground and background of the test renders
+///
+#[derive(Debug, Clone, Copy)]
+pub struct RendererColors {
+    pub fg_color: Color,
+    pub bg_color: Color,
+}
+
 impl Color {
     pub fn white() -> Self {
         Self {
@@ -46,3 +58,52 @@ impl Color {
         }
     }
 }
+
+///
+/// sRGB transfer function, see: https://en.wikipedia.org/wiki/SRGB
+///
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        f32::powf((value + 0.055) / 1.055, 2.4)
+    }
+}
+
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * f32::powf(value, 1.0 / 2.4) - 0.055
+    }
+}
+
+impl Color {
+    ///
+    /// Decodes the color components, alpha is left as is
+    ///
+    pub fn to_linear(&self) -> Self {
+        Self {
+            r: srgb_to_linear(self.r),
+            g: srgb_to_linear(self.g),
+            b: srgb_to_linear(self.b),
+            a: self.a,
+        }
+    }
+
+    pub fn to_srgb(&self) -> Self {
+        Self {
+            r: linear_to_srgb(self.r),
+            g: linear_to_srgb(self.g),
+            b: linear_to_srgb(self.b),
+            a: self.a,
+        }
+    }
+
+    ///
+    /// Relative luminance, expects linear components
+    ///
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+}
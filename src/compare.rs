@@ -0,0 +1,95 @@
+use crate::rasterizer::{f32_to_u8, NUM_CHANNELS};
+
+///
+/// Differences between two RGBA buffers as returned by `Rasterizer::render`
+///
+#[derive(Debug, Clone)]
+pub struct ImageDiff {
+    pub width: usize,
+    pub height: usize,
+    ///
+    /// Peak signal to noise ratio over the color channels in dB, infinite for identical images
+    ///
+    pub psnr: f64,
+    ///
+    /// Largest difference on any channel, between 0.0 and 1.0
+    ///
+    pub max_difference: f32,
+    ///
+    /// Pixels that differ once converted to 8 bits, as they would be saved
+    ///
+    pub differing_pixels: usize,
+    ///
+    /// RGBA8 heatmap of the per pixel differences, multiplied by the amplification
+    ///
+    pub heatmap: Vec<u8>,
+}
+
+///
+/// Compares two renders of the same size. Differences are multiplied by `amplify` in the
+/// heatmap so that the ones below 1/255 still show up.
+///
+pub fn compare_renders(
+    a: &[f32],
+    b: &[f32],
+    width: usize,
+    height: usize,
+    amplify: f32,
+) -> ImageDiff {
+    assert_eq!(a.len(), width * height * NUM_CHANNELS);
+    assert_eq!(b.len(), a.len());
+
+    let mut squared_sum = 0.0_f64;
+    let mut max_difference = 0.0_f32;
+    let mut differing_pixels = 0;
+    let mut heatmap = Vec::<u8>::with_capacity(a.len());
+
+    for (pa, pb) in a.chunks(NUM_CHANNELS).zip(b.chunks(NUM_CHANNELS)) {
+        let mut pixel_difference = 0.0_f32;
+
+        for channel in 0..3 {
+            let difference = (pa[channel] - pb[channel]).abs();
+
+            squared_sum += (difference as f64) * (difference as f64);
+            pixel_difference = pixel_difference.max(difference);
+        }
+
+        pixel_difference = pixel_difference.max((pa[3] - pb[3]).abs());
+        max_difference = max_difference.max(pixel_difference);
+
+        if pa
+            .iter()
+            .zip(pb)
+            .any(|(x, y)| f32_to_u8(*x) != f32_to_u8(*y))
+        {
+            differing_pixels += 1;
+        }
+
+        heatmap.extend_from_slice(&heat_color(pixel_difference * amplify));
+    }
+
+    let mse = squared_sum / (width * height * 3).max(1) as f64;
+
+    ImageDiff {
+        width,
+        height,
+        psnr: 10.0 * (1.0 / mse).log10(),
+        max_difference,
+        differing_pixels,
+        heatmap,
+    }
+}
+
+///
+/// Black through red and yellow to white as `value` goes from 0.0 to 1.0
+///
+fn heat_color(value: f32) -> [u8; 4] {
+    let value = value.clamp(0.0, 1.0) * 3.0;
+
+    [
+        f32_to_u8(value.min(1.0)),
+        f32_to_u8((value - 1.0).clamp(0.0, 1.0)),
+        f32_to_u8((value - 2.0).clamp(0.0, 1.0)),
+        255,
+    ]
+}
@@ -0,0 +1,275 @@
+//!
+//! Writers for rendered buffers and coverage masks. Netpbm (PPM, PGM, PAM) and raw dumps are
+//! built in, PNG goes through the `image` crate when the `image` feature is enabled. `encode`
+//! returns the bytes in memory, `save` picks the format from the file extension.
+//!
+
+use crate::rasterizer::NUM_CHANNELS;
+
+///
+/// Output file formats. Netpbm formats store 16-bit samples big endian, as the spec requires.
+///
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    ///
+    /// Binary PPM (P6), RGB without the alpha channel
+    ///
+    Ppm,
+    ///
+    /// Plain PPM (P3), decimal samples with one image row per line so that outputs diff as text
+    ///
+    PlainPpm,
+    ///
+    /// Binary PGM (P5), masks only
+    ///
+    Pgm,
+    ///
+    /// Plain PGM (P2), masks only, one image row per line
+    ///
+    PlainPgm,
+    ///
+    /// PAM (P7), RGB_ALPHA or GRAYSCALE depending on the pixels
+    ///
+    Pam,
+    ///
+    /// The samples without any header, 16-bit ones little endian
+    ///
+    Raw,
+    #[cfg(feature = "image")]
+    Png,
+}
+
+///
+/// Pixels to encode, row by row without padding
+///
+#[derive(Debug, Clone, Copy)]
+pub enum Pixels<'a> {
+    ///
+    /// 4 bytes per pixel, as returned by `Rasterizer::render_rgba8`
+    ///
+    Rgba8(&'a [u8]),
+    ///
+    /// One byte per pixel, as returned by `Rasterizer::coverage_mask_u8`
+    ///
+    Alpha8(&'a [u8]),
+    ///
+    /// One sample per pixel, as returned by `Rasterizer::coverage_mask_u16`
+    ///
+    Alpha16(&'a [u16]),
+}
+
+impl Format {
+    ///
+    /// Format for the extension of `path`: ppm, pgm, pam, raw, rgba or alpha, and png when
+    /// the `image` feature is enabled. Plain Netpbm has no extension of its own.
+    ///
+    pub fn from_path(path: &str) -> Option<Format> {
+        let extension = std::path::Path::new(path).extension()?.to_str()?;
+
+        match extension.to_ascii_lowercase().as_str() {
+            "ppm" => Some(Format::Ppm),
+            "pgm" => Some(Format::Pgm),
+            "pam" => Some(Format::Pam),
+            "raw" | "rgba" | "alpha" => Some(Format::Raw),
+            #[cfg(feature = "image")]
+            "png" => Some(Format::Png),
+            _ => None,
+        }
+    }
+}
+
+impl Pixels<'_> {
+    fn channels(&self) -> usize {
+        match self {
+            Pixels::Rgba8(_) => NUM_CHANNELS,
+            Pixels::Alpha8(_) | Pixels::Alpha16(_) => 1,
+        }
+    }
+
+    fn max_value(&self) -> u16 {
+        match self {
+            Pixels::Rgba8(_) | Pixels::Alpha8(_) => u8::MAX as u16,
+            Pixels::Alpha16(_) => u16::MAX,
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Pixels::Rgba8(samples) | Pixels::Alpha8(samples) => samples.len(),
+            Pixels::Alpha16(samples) => samples.len(),
+        }
+    }
+
+    fn sample(&self, index: usize) -> u16 {
+        match self {
+            Pixels::Rgba8(samples) | Pixels::Alpha8(samples) => samples[index] as u16,
+            Pixels::Alpha16(samples) => samples[index],
+        }
+    }
+
+    ///
+    /// Sample `channel` of pixel `index`, masks repeat their single channel
+    ///
+    fn channel(&self, index: usize, channel: usize) -> u16 {
+        match self {
+            Pixels::Rgba8(_) => self.sample(index * NUM_CHANNELS + channel),
+            Pixels::Alpha8(_) | Pixels::Alpha16(_) => self.sample(index),
+        }
+    }
+
+    fn push_binary(&self, value: u16, output: &mut Vec<u8>) {
+        match self {
+            Pixels::Rgba8(_) | Pixels::Alpha8(_) => output.push(value as u8),
+            Pixels::Alpha16(_) => output.extend_from_slice(&value.to_be_bytes()),
+        }
+    }
+}
+
+///
+/// Encodes `pixels` of a `width` × `height` image in `format`. RGBA pixels can't be stored as
+/// PGM, masks stored as PPM are gray.
+///
+pub fn encode(
+    pixels: Pixels,
+    width: usize,
+    height: usize,
+    format: Format,
+) -> Result<Vec<u8>, String> {
+    if pixels.len() != width * height * pixels.channels() {
+        return Err(format!(
+            "{} samples for a {}x{} image with {} channels",
+            pixels.len(),
+            width,
+            height,
+            pixels.channels()
+        ));
+    }
+
+    let count = width * height;
+    let max_value = pixels.max_value();
+    let netpbm_header =
+        |magic: &str| format!("{}\n{} {}\n{}\n", magic, width, height, max_value).into_bytes();
+    let mut output = Vec::new();
+
+    match format {
+        Format::Ppm => {
+            output = netpbm_header("P6");
+
+            for index in 0..count {
+                for channel in 0..3 {
+                    pixels.push_binary(pixels.channel(index, channel), &mut output);
+                }
+            }
+        }
+        Format::PlainPpm => {
+            output = netpbm_header("P3");
+            push_plain_rows(width, count, 3, &pixels, &mut output);
+        }
+        Format::Pgm | Format::PlainPgm if pixels.channels() != 1 => {
+            return Err("PGM only holds masks, use PPM or PAM for RGBA".to_string());
+        }
+        Format::Pgm => {
+            output = netpbm_header("P5");
+
+            for index in 0..count {
+                pixels.push_binary(pixels.sample(index), &mut output);
+            }
+        }
+        Format::PlainPgm => {
+            output = netpbm_header("P2");
+            push_plain_rows(width, count, 1, &pixels, &mut output);
+        }
+        Format::Pam => {
+            let (depth, tuple_type) = match pixels.channels() {
+                1 => (1, "GRAYSCALE"),
+                _ => (NUM_CHANNELS, "RGB_ALPHA"),
+            };
+
+            output = format!(
+                "P7\nWIDTH {}\nHEIGHT {}\nDEPTH {}\nMAXVAL {}\nTUPLTYPE {}\nENDHDR\n",
+                width, height, depth, max_value, tuple_type
+            )
+            .into_bytes();
+
+            for index in 0..pixels.len() {
+                pixels.push_binary(pixels.sample(index), &mut output);
+            }
+        }
+        Format::Raw => match pixels {
+            Pixels::Rgba8(samples) | Pixels::Alpha8(samples) => {
+                output.extend_from_slice(samples);
+            }
+            Pixels::Alpha16(samples) => {
+                for sample in samples {
+                    output.extend_from_slice(&sample.to_le_bytes());
+                }
+            }
+        },
+        #[cfg(feature = "image")]
+        Format::Png => {
+            let color_type = match pixels {
+                Pixels::Rgba8(_) => image::ColorType::Rgba8,
+                Pixels::Alpha8(_) => image::ColorType::L8,
+                Pixels::Alpha16(_) => image::ColorType::L16,
+            };
+            // PNG is big endian too
+            let mut samples = Vec::new();
+
+            for index in 0..pixels.len() {
+                pixels.push_binary(pixels.sample(index), &mut samples);
+            }
+
+            image::codecs::png::PngEncoder::new(&mut output)
+                .encode(&samples, width as u32, height as u32, color_type)
+                .map_err(|error| error.to_string())?;
+        }
+    }
+
+    Ok(output)
+}
+
+fn push_plain_rows(
+    width: usize,
+    count: usize,
+    channels: usize,
+    pixels: &Pixels,
+    output: &mut Vec<u8>,
+) {
+    let mut line = String::new();
+
+    for index in 0..count {
+        for channel in 0..channels {
+            if !line.is_empty() {
+                line.push(' ');
+            }
+
+            line.push_str(pixels.channel(index, channel).to_string().as_str());
+        }
+
+        if (index + 1) % width == 0 {
+            line.push('\n');
+            output.extend_from_slice(line.as_bytes());
+            line.clear();
+        }
+    }
+}
+
+///
+/// Encodes `pixels` in the format matching the extension of `path` and writes the file
+///
+pub fn save(path: &str, pixels: Pixels, width: usize, height: usize) -> Result<(), String> {
+    let format = Format::from_path(path).ok_or_else(|| {
+        format!(
+            "{}: unknown extension, expected ppm, pgm, pam, raw, rgba, alpha{}",
+            path,
+            if cfg!(feature = "image") {
+                " or png"
+            } else {
+                ""
+            }
+        )
+    })?;
+    let bytes = encode(pixels, width, height, format)?;
+
+    std::fs::write(path, bytes).map_err(|error| format!("{}: {}", path, error))
+}
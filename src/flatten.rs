@@ -0,0 +1,520 @@
+use alloc::vec::Vec;
+
+use crate::geometry::{approximate_integral, approximate_inverse_integral};
+#[cfg(all(feature = "libm", not(any(feature = "std", test))))]
+use crate::math::FloatExt;
+use crate::{Point, QuadraticBezier, SubdivisionMethod};
+
+///
+/// Largest number of segments a curve is split into, by every flattener. A zero tolerance asks
+/// for infinitely many, which would overflow or never finish.
+///
+pub const MAX_SEGMENTS: usize = 1 << 12;
+
+///
+/// Deepest halving of the parameter interval, which gives `MAX_SEGMENTS` intervals
+///
+const MAX_DEPTH: u32 = MAX_SEGMENTS.trailing_zeros();
+
+///
+/// Rounds a segment count up to a whole number in [1, MAX_SEGMENTS]. A NaN count, from NaN
+/// coordinates or 0 / 0, gives a single segment.
+///
+pub(crate) fn bounded_segments(count: f32) -> usize {
+    if count.is_nan() {
+        1
+    } else {
+        f32::ceil(count).clamp(1.0, MAX_SEGMENTS as f32) as usize
+    }
+}
+
+///
+/// Receives the line segments of a flattened outline, one at a time
+///
+pub trait LineSink {
+    fn line(&mut self, p0: Point, p1: Point);
+
+    ///
+    /// Sends the segments between consecutive points
+    ///
+    fn polyline(&mut self, points: impl IntoIterator<Item = Point>)
+    where
+        Self: Sized,
+    {
+        let mut points = points.into_iter();
+
+        if let Some(mut previous) = points.next() {
+            for p in points {
+                self.line(previous, p);
+                previous = p;
+            }
+        }
+    }
+}
+
+///
+/// Receives flattened subpaths: `move_to`, at least one `line_to`, then `end`.
+/// Subpaths made of a single point are never sent.
+///
+pub trait PathSink {
+    fn move_to(&mut self, p: Point);
+    fn line_to(&mut self, p: Point);
+    ///
+    /// `closed` tells whether the subpath ended with a close command
+    ///
+    fn end(&mut self, closed: bool);
+}
+
+impl LineSink for Vec<(Point, Point)> {
+    fn line(&mut self, p0: Point, p1: Point) {
+        self.push((p0, p1));
+    }
+}
+
+///
+/// Turns the subpaths into lines for a `LineSink`, closing every subpath with a line back to
+/// its first point as needed for filling
+///
+pub struct ClosingSink<'a, S: LineSink> {
+    sink: &'a mut S,
+    first: Point,
+    current: Point,
+}
+
+impl<'a, S: LineSink> ClosingSink<'a, S> {
+    pub fn new(sink: &'a mut S) -> Self {
+        let origin = Point { x: 0.0, y: 0.0 };
+
+        Self {
+            sink,
+            first: origin,
+            current: origin,
+        }
+    }
+}
+
+impl<S: LineSink> PathSink for ClosingSink<'_, S> {
+    fn move_to(&mut self, p: Point) {
+        self.first = p;
+        self.current = p;
+    }
+
+    fn line_to(&mut self, p: Point) {
+        self.sink.line(self.current, p);
+        self.current = p;
+    }
+
+    fn end(&mut self, _closed: bool) {
+        self.sink.line(self.current, self.first);
+    }
+}
+
+///
+/// Points of `QuadraticBezier::smart_subdivide`, evaluated on the fly
+///
+#[derive(Debug, Clone)]
+pub struct ParabolaPoints {
+    q: QuadraticBezier,
+    a0: f32,
+    a2: f32,
+    u0: f32,
+    u2: f32,
+    n: f32,
+    segments: i32,
+    i: i32,
+}
+
+impl ParabolaPoints {
+    pub fn new(q: QuadraticBezier, tolerance: f32) -> Self {
+        let params = q.map_to_basic();
+        let a0 = approximate_integral(params.x0);
+        let a2 = approximate_integral(params.x2);
+        let segments = bounded_segments(params.segment_count(a0, a2, tolerance));
+
+        Self {
+            q,
+            a0,
+            a2,
+            u0: approximate_inverse_integral(a0),
+            u2: approximate_inverse_integral(a2),
+            n: segments as f32,
+            segments: segments as i32,
+            i: 0,
+        }
+    }
+}
+
+impl Iterator for ParabolaPoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        let i = self.i;
+
+        if i > self.segments {
+            return None;
+        }
+
+        self.i += 1;
+
+        let t = if i == 0 {
+            0.0
+        } else if i == self.segments {
+            1.0
+        } else {
+            let u =
+                approximate_inverse_integral(self.a0 + ((self.a2 - self.a0) * (i as f32)) / self.n);
+            (u - self.u0) / (self.u2 - self.u0)
+        };
+
+        Some(self.q.eval(t))
+    }
+}
+
+///
+/// Points of `QuadraticBezier::recursive_subdivide`, evaluated on the fly. The recursion is
+/// replaced by a walk over the dyadic intervals [k / 2^depth, (k + 1) / 2^depth], which gives
+/// the same parameters without a stack.
+///
+#[derive(Debug, Clone)]
+pub struct DeCasteljauPoints {
+    q: QuadraticBezier,
+    tolerance: f32,
+    k: u32,
+    depth: u32,
+    started: bool,
+    done: bool,
+}
+
+impl DeCasteljauPoints {
+    pub fn new(q: QuadraticBezier, tolerance: f32) -> Self {
+        Self {
+            q,
+            tolerance,
+            k: 0,
+            depth: 0,
+            started: false,
+            done: false,
+        }
+    }
+}
+
+impl Iterator for DeCasteljauPoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if !self.started {
+            self.started = true;
+            return Some(self.q.eval(0.0));
+        }
+
+        if self.done {
+            return None;
+        }
+
+        loop {
+            let scale = (1_u32 << self.depth) as f32;
+            let t0 = self.k as f32 / scale;
+            let t1 = (self.k + 1) as f32 / scale;
+
+            if self.depth >= MAX_DEPTH || self.q.subsegment(t0, t1).error() <= self.tolerance {
+                // Move to the next interval, going up while this one was a right half
+                self.k += 1;
+
+                while self.k.is_multiple_of(2) && self.depth > 0 {
+                    self.k /= 2;
+                    self.depth -= 1;
+                }
+
+                self.done = self.depth == 0;
+                return Some(self.q.eval(t1));
+            }
+
+            self.k *= 2;
+            self.depth += 1;
+        }
+    }
+}
+
+///
+/// Points at `segments` evenly spaced values of t, up to `MAX_SEGMENTS`
+///
+#[derive(Debug, Clone)]
+pub struct UniformPoints {
+    q: QuadraticBezier,
+    segments: usize,
+    i: usize,
+}
+
+impl UniformPoints {
+    pub fn new(q: QuadraticBezier, segments: usize) -> Self {
+        Self {
+            q,
+            segments: segments.clamp(1, MAX_SEGMENTS),
+            i: 0,
+        }
+    }
+}
+
+impl Iterator for UniformPoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if self.i > self.segments {
+            return None;
+        }
+
+        let t = self.i as f32 / self.segments as f32;
+        self.i += 1;
+
+        Some(self.q.eval(t))
+    }
+}
+
+///
+/// Points of the adaptive forward differencing walk, see `ForwardDifferencing`
+///
+#[derive(Debug, Clone)]
+pub struct ForwardDifferencePoints {
+    p: Point,
+    d1: Point,
+    d2: Point,
+    end: Point,
+    remaining: u32,
+    started: bool,
+}
+
+impl ForwardDifferencePoints {
+    pub fn new(q: QuadraticBezier, tolerance: f32) -> Self {
+        let [p0, p1, p2] = q.points();
+        // B(t) = a * t^2 + b * t + p0
+        let a = Point {
+            x: p0.x - 2.0 * p1.x + p2.x,
+            y: p0.y - 2.0 * p1.y + p2.y,
+        };
+        let b = Point {
+            x: 2.0 * (p1.x - p0.x),
+            y: 2.0 * (p1.y - p0.y),
+        };
+        // Forward differences for a step of 1: B(1) - B(0) and the constant second difference
+        let mut d1 = Point {
+            x: a.x + b.x,
+            y: a.y + b.y,
+        };
+        let mut d2 = Point {
+            x: 2.0 * a.x,
+            y: 2.0 * a.y,
+        };
+        let mut depth = 0;
+
+        // A step of h strays |a| * h^2 / 4 = |d2| / 8 from its chord, halve it until that fits
+        while f32::hypot(d2.x, d2.y) / 8.0 > tolerance && depth < MAX_DEPTH {
+            d2 = Point {
+                x: d2.x * 0.25,
+                y: d2.y * 0.25,
+            };
+            d1 = Point {
+                x: 0.5 * d1.x - 0.5 * d2.x,
+                y: 0.5 * d1.y - 0.5 * d2.y,
+            };
+            depth += 1;
+        }
+
+        Self {
+            p: p0,
+            d1,
+            d2,
+            end: p2,
+            remaining: 1 << depth,
+            started: false,
+        }
+    }
+}
+
+impl Iterator for ForwardDifferencePoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        if !self.started {
+            self.started = true;
+            return Some(self.p);
+        }
+
+        match self.remaining {
+            0 => None,
+            1 => {
+                // Land exactly on the end point instead of the accumulated one
+                self.remaining = 0;
+                Some(self.end)
+            }
+            _ => {
+                self.remaining -= 1;
+                self.p = Point {
+                    x: self.p.x + self.d1.x,
+                    y: self.p.y + self.d1.y,
+                };
+                self.d1 = Point {
+                    x: self.d1.x + self.d2.x,
+                    y: self.d1.y + self.d2.y,
+                };
+                Some(self.p)
+            }
+        }
+    }
+}
+
+///
+/// Algorithm approximating a quadratic by a polyline. Anything implementing it can be passed
+/// where a `SubdivisionMethod` is accepted, so other algorithms can be plugged in.
+///
+pub trait Flattener {
+    type Points: Iterator<Item = Point>;
+
+    ///
+    /// Points of the polyline approximating `q` within `tolerance`, from its start to its end
+    ///
+    fn points(&self, q: QuadraticBezier, tolerance: f32) -> Self::Points;
+}
+
+impl<F: Flattener> Flattener for &F {
+    type Points = F::Points;
+
+    fn points(&self, q: QuadraticBezier, tolerance: f32) -> Self::Points {
+        (**self).points(q, tolerance)
+    }
+}
+
+///
+/// Recursive subdivision at t = 0.5 until the control point is close enough to the chord
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeCasteljau;
+
+///
+/// Levien's parabola approximation, segments are spread evenly along the integral of the
+/// curvature
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParabolaApprox;
+
+///
+/// Fixed number of segments at evenly spaced t, whatever the tolerance, up to `MAX_SEGMENTS`
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Uniform {
+    pub segments: usize,
+}
+
+///
+/// Evenly spaced t, with the segment count given by Wang's formula for the second
+/// differences of the control points: n = ceil(sqrt(|p0 - 2 * p1 + p2| / (4 * tolerance)))
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Wang;
+
+///
+/// Adaptive forward differencing: the step is halved until one segment fits in the tolerance,
+/// then every point is found with two additions. The second difference of a quadratic only
+/// depends on the step, so the step chosen for the first segment holds for the whole curve.
+///
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ForwardDifferencing;
+
+impl Flattener for DeCasteljau {
+    type Points = DeCasteljauPoints;
+
+    fn points(&self, q: QuadraticBezier, tolerance: f32) -> Self::Points {
+        DeCasteljauPoints::new(q, tolerance)
+    }
+}
+
+impl Flattener for ParabolaApprox {
+    type Points = ParabolaPoints;
+
+    fn points(&self, q: QuadraticBezier, tolerance: f32) -> Self::Points {
+        ParabolaPoints::new(q, tolerance)
+    }
+}
+
+impl Flattener for Uniform {
+    type Points = UniformPoints;
+
+    fn points(&self, q: QuadraticBezier, _tolerance: f32) -> Self::Points {
+        UniformPoints::new(q, self.segments)
+    }
+}
+
+impl Flattener for Wang {
+    type Points = UniformPoints;
+
+    fn points(&self, q: QuadraticBezier, tolerance: f32) -> Self::Points {
+        let [p0, p1, p2] = q.points();
+        let dd = f32::hypot(p0.x - 2.0 * p1.x + p2.x, p0.y - 2.0 * p1.y + p2.y);
+
+        // A zero tolerance gives an infinite count, capped like the other flatteners
+        UniformPoints::new(q, bounded_segments(f32::sqrt(dd / (4.0 * tolerance))))
+    }
+}
+
+impl Flattener for ForwardDifferencing {
+    type Points = ForwardDifferencePoints;
+
+    fn points(&self, q: QuadraticBezier, tolerance: f32) -> Self::Points {
+        ForwardDifferencePoints::new(q, tolerance)
+    }
+}
+
+///
+/// Points of a quadratic flattened with any of the built-in methods
+///
+#[derive(Debug, Clone)]
+pub enum QuadraticPoints {
+    DeCasteljau(DeCasteljauPoints),
+    ParabolaApprox(ParabolaPoints),
+    Uniform(UniformPoints),
+    ForwardDifferencing(ForwardDifferencePoints),
+}
+
+impl Iterator for QuadraticPoints {
+    type Item = Point;
+
+    fn next(&mut self) -> Option<Point> {
+        match self {
+            QuadraticPoints::DeCasteljau(points) => points.next(),
+            QuadraticPoints::ParabolaApprox(points) => points.next(),
+            QuadraticPoints::Uniform(points) => points.next(),
+            QuadraticPoints::ForwardDifferencing(points) => points.next(),
+        }
+    }
+}
+
+impl Flattener for SubdivisionMethod {
+    type Points = QuadraticPoints;
+
+    fn points(&self, q: QuadraticBezier, tolerance: f32) -> Self::Points {
+        match *self {
+            SubdivisionMethod::DeCasteljau => {
+                QuadraticPoints::DeCasteljau(DeCasteljau.points(q, tolerance))
+            }
+            SubdivisionMethod::ParabolaApprox => {
+                QuadraticPoints::ParabolaApprox(ParabolaApprox.points(q, tolerance))
+            }
+            SubdivisionMethod::Uniform { segments } => {
+                QuadraticPoints::Uniform(Uniform { segments }.points(q, tolerance))
+            }
+            SubdivisionMethod::Wang => QuadraticPoints::Uniform(Wang.points(q, tolerance)),
+            SubdivisionMethod::ForwardDifferencing => {
+                QuadraticPoints::ForwardDifferencing(ForwardDifferencing.points(q, tolerance))
+            }
+        }
+    }
+}
+
+impl QuadraticBezier {
+    ///
+    /// Streams the points of the polyline approximating the curve, from its start to its end,
+    /// without allocating
+    ///
+    pub fn flatten<F: Flattener>(&self, tolerance: f32, flattener: F) -> F::Points {
+        flattener.points(*self, tolerance)
+    }
+}
@@ -1,11 +1,8 @@
-use crate::{Color, Point, QuadraticBezier, Rasterizer, SubdivisionMethod};
+use crate::path::{CubicQuadratics, Path};
+use crate::{Point, QuadraticBezier, Rasterizer, SubdivisionMethod};
 use ttf_parser as ttf;
 
-#[derive(Debug, Clone, Copy)]
-pub struct RendererColors {
-    pub fg_color: Color,
-    pub bg_color: Color,
-}
+pub use crate::color::RendererColors;
 
 const FONT_SIZE: f64 = 128.0;
 
@@ -66,6 +63,14 @@ struct OutlineBuilder {
     min_y: f32,
     starting_point: Point,
     subdivision_method: SubdivisionMethod,
+    ///
+    /// Draws the quadratics with `draw_quadratic_exact` instead of flattening them
+    ///
+    exact: bool,
+    ///
+    /// Prints every outline command when set
+    ///
+    verbose: bool,
 }
 
 impl Default for OutlineBuilder {
@@ -77,6 +82,8 @@ impl Default for OutlineBuilder {
             min_y: 0.0,
             starting_point: Point { x: 0.0, y: 0.0 },
             subdivision_method: SubdivisionMethod::ParabolaApprox,
+            exact: false,
+            verbose: true,
         }
     }
 }
@@ -101,6 +108,8 @@ impl OutlineBuilder {
                 y: f32::MIN,
             },
             subdivision_method: method,
+            exact: false,
+            verbose: true,
         }
     }
 }
@@ -114,7 +123,9 @@ impl ttf::OutlineBuilder for OutlineBuilder {
         self.prev_point = new_point;
         self.starting_point = new_point;
 
-        println!("Move to: (x={}, y={})", new_point.x, new_point.y);
+        if self.verbose {
+            println!("Move to: (x={}, y={})", new_point.x, new_point.y);
+        }
     }
 
     fn line_to(&mut self, x: f32, y: f32) {
@@ -125,7 +136,9 @@ impl ttf::OutlineBuilder for OutlineBuilder {
         self.rasterizer.draw_line(self.prev_point, new_point);
         self.prev_point = new_point;
 
-        println!("Line to: (x={}, y={})", new_point.x, new_point.y);
+        if self.verbose {
+            println!("Line to: (x={}, y={})", new_point.x, new_point.y);
+        }
     }
 
     fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
@@ -138,13 +151,19 @@ impl ttf::OutlineBuilder for OutlineBuilder {
             y: self.rasterizer.height as f32 - (y - self.min_y),
         };
         let q = QuadraticBezier::new(self.prev_point, p1, p);
-        self.rasterizer.draw_quadratic(q, self.subdivision_method);
+        if self.exact {
+            self.rasterizer.draw_quadratic_exact(q);
+        } else {
+            self.rasterizer.draw_quadratic(q, self.subdivision_method);
+        }
         self.prev_point = p;
 
-        println!(
-            "Quad to: (x1={}, y1={}), (x={}, y={})",
-            p1.x, p1.y, p.x, p.y
-        );
+        if self.verbose {
+            println!(
+                "Quad to: (x1={}, y1={}), (x={}, y={})",
+                p1.x, p1.y, p.x, p.y
+            );
+        }
     }
 
     fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
@@ -159,10 +178,236 @@ impl ttf::OutlineBuilder for OutlineBuilder {
             .draw_line(self.prev_point, self.starting_point);
         self.prev_point = self.starting_point;
 
-        println!("CLOSE");
+        if self.verbose {
+            println!("CLOSE");
+        }
+    }
+}
+
+///
+/// Builds the glyph outline as a `Path`, placed on the canvas as `OutlineBuilder` draws it
+///
+struct PathOutlineBuilder {
+    path: Path,
+    min_x: f32,
+    min_y: f32,
+    height: f32,
+}
+
+impl PathOutlineBuilder {
+    fn point(&self, x: f32, y: f32) -> Point {
+        Point {
+            x: x - self.min_x,
+            y: self.height - (y - self.min_y),
+        }
+    }
+}
+
+impl ttf::OutlineBuilder for PathOutlineBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.path.move_to(self.point(x, y));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.path.line_to(self.point(x, y));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        self.path.quad_to(self.point(x1, y1), self.point(x, y));
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        self.path
+            .cubic_to(self.point(x1, y1), self.point(x2, y2), self.point(x, y));
+    }
+
+    fn close(&mut self) {
+        self.path.close();
+    }
+}
+
+///
+/// Gathers the quadratic segments of a glyph outline, lines are skipped
+///
+struct QuadraticCollector {
+    prev_point: Point,
+    quadratics: Vec<QuadraticBezier>,
+}
+
+impl ttf::OutlineBuilder for QuadraticCollector {
+    fn move_to(&mut self, x: f32, y: f32) {
+        self.prev_point = Point { x, y };
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        self.prev_point = Point { x, y };
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let p = Point { x, y };
+        self.quadratics.push(QuadraticBezier::new(
+            self.prev_point,
+            Point { x: x1, y: y1 },
+            p,
+        ));
+        self.prev_point = p;
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let p = Point { x, y };
+        self.quadratics.extend(CubicQuadratics::new(
+            self.prev_point,
+            Point { x: x1, y: y1 },
+            Point { x: x2, y: y2 },
+            p,
+            CUBIC_TOLERANCE,
+        ));
+        self.prev_point = p;
+    }
+
+    fn close(&mut self) {}
+}
+
+///
+/// Font units, cubics of CFF fonts are split into quadratics this close to the original
+///
+const CUBIC_TOLERANCE: f32 = 0.1;
+
+///
+/// Quadratic segments of every glyph in the font, in font units
+///
+pub fn font_quadratics(font_path: &str) -> Result<Vec<QuadraticBezier>, String> {
+    let font_data =
+        std::fs::read(font_path).map_err(|error| format!("{}: {}", font_path, error))?;
+    let face = ttf::Face::from_slice(&font_data, 0)
+        .map_err(|error| format!("{}: {}", font_path, error))?;
+    let mut collector = QuadraticCollector {
+        prev_point: Point { x: 0.0, y: 0.0 },
+        quadratics: Vec::new(),
+    };
+
+    for glyph_index in 0..face.number_of_glyphs() {
+        face.outline_glyph(ttf::GlyphId(glyph_index), &mut collector);
+    }
+
+    Ok(collector.quadratics)
+}
+
+///
+/// Rasterizes the glyph at one pixel per font unit, the canvas is the size of its bounding box
+///
+fn outline_glyph(
+    face: &ttf::Face,
+    glyph_id: ttf::GlyphId,
+    tolerance: f32,
+    method: SubdivisionMethod,
+    exact: bool,
+    verbose: bool,
+) -> Option<(OutlineBuilder, ttf::Rect)> {
+    let mut bbox_builder = BboxOutlineBuilder {
+        min_x: 0.0,
+        max_x: 0.0,
+        min_y: 0.0,
+        max_y: 0.0,
+    };
+    face.outline_glyph(glyph_id, &mut bbox_builder)?;
+
+    let mut builder = OutlineBuilder {
+        exact,
+        verbose,
+        ..OutlineBuilder::new(tolerance, &bbox_builder, method)
+    };
+    let bbox = face.outline_glyph(glyph_id, &mut builder)?;
+
+    Some((builder, bbox))
+}
+
+///
+/// Same rasterizer as the one `glyph_test` renders, without printing the outline
+///
+pub fn rasterize_glyph(
+    font_path: &str,
+    glyph_index: u16,
+    tolerance: f32,
+    method: SubdivisionMethod,
+) -> Result<Rasterizer, String> {
+    rasterize_glyph_with(font_path, glyph_index, tolerance, method, false)
+}
+
+///
+/// Same as `rasterize_glyph` with the curves drawn by `Rasterizer::draw_quadratic_exact`
+///
+pub fn rasterize_glyph_exact(font_path: &str, glyph_index: u16) -> Result<Rasterizer, String> {
+    rasterize_glyph_with(
+        font_path,
+        glyph_index,
+        0.0,
+        SubdivisionMethod::ParabolaApprox,
+        true,
+    )
+}
+
+fn rasterize_glyph_with(
+    font_path: &str,
+    glyph_index: u16,
+    tolerance: f32,
+    method: SubdivisionMethod,
+    exact: bool,
+) -> Result<Rasterizer, String> {
+    let font_data =
+        std::fs::read(font_path).map_err(|error| format!("{}: {}", font_path, error))?;
+    let face = ttf::Face::from_slice(&font_data, 0)
+        .map_err(|error| format!("{}: {}", font_path, error))?;
+
+    match outline_glyph(
+        &face,
+        ttf::GlyphId(glyph_index),
+        tolerance,
+        method,
+        exact,
+        false,
+    ) {
+        Some((builder, _)) => Ok(builder.rasterizer),
+        None => Err(format!(
+            "{}: glyph {} has no outline",
+            font_path, glyph_index
+        )),
     }
 }
 
+///
+/// Outline of the glyph with the canvas size of `rasterize_glyph`, as (path, width, height)
+///
+pub fn glyph_path(font_path: &str, glyph_index: u16) -> Result<(Path, usize, usize), String> {
+    let font_data =
+        std::fs::read(font_path).map_err(|error| format!("{}: {}", font_path, error))?;
+    let face = ttf::Face::from_slice(&font_data, 0)
+        .map_err(|error| format!("{}: {}", font_path, error))?;
+    let glyph_id = ttf::GlyphId(glyph_index);
+    let no_outline = || format!("{}: glyph {} has no outline", font_path, glyph_index);
+    let mut bbox = BboxOutlineBuilder {
+        min_x: 0.0,
+        max_x: 0.0,
+        min_y: 0.0,
+        max_y: 0.0,
+    };
+    face.outline_glyph(glyph_id, &mut bbox)
+        .ok_or_else(no_outline)?;
+
+    let width = (bbox.max_x - bbox.min_x).ceil() as usize + 1;
+    let height = (bbox.max_y - bbox.min_y).ceil() as usize + 1;
+    let mut builder = PathOutlineBuilder {
+        path: Path::new(),
+        min_x: bbox.min_x,
+        min_y: bbox.min_y,
+        height: height as f32,
+    };
+    face.outline_glyph(glyph_id, &mut builder)
+        .ok_or_else(no_outline)?;
+
+    Ok((builder.path, width, height))
+}
+
 pub fn glyph_test(
     font_path: &str,
     glyph_index: u16,
@@ -186,32 +431,17 @@ pub fn glyph_test(
 
     let glyph_id = ttf::GlyphId(glyph_index);
     let glyph_to_path = |face: &ttf::Face, glyph_id: ttf::GlyphId, method: SubdivisionMethod| {
-        let mut bbox_builder = BboxOutlineBuilder {
-            min_x: 0.0,
-            max_x: 0.0,
-            min_y: 0.0,
-            max_y: 0.0,
-        };
-        let _ = match face.outline_glyph(glyph_id, &mut bbox_builder) {
-            Some(v) => v,
-            None => return,
-        };
-        let mut builder = OutlineBuilder::new(tolerance, &bbox_builder, method);
-        let bbox = match face.outline_glyph(glyph_id, &mut builder) {
+        let (builder, bbox) = match outline_glyph(face, glyph_id, tolerance, method, false, true) {
             Some(v) => v,
             None => return,
         };
 
         println!("BBOX: {:?}", bbox);
 
-        let subdivision_str = match method {
-            SubdivisionMethod::DeCasteljau => "recursive",
-            SubdivisionMethod::ParabolaApprox => "smart",
-        };
-
         let output = format!(
             "glyph_{}_{}_subdivision_test.png",
-            glyph_index, subdivision_str,
+            glyph_index,
+            method.name(),
         );
         // render_to(&builder.rasterizer, output.as_str(), colors);
         save_to(&builder.rasterizer, output.as_str(), colors);
@@ -1,4 +1,11 @@
-pub fn clamp<T: std::cmp::PartialOrd>(value: T, min: T, max: T) -> T {
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::flatten::{bounded_segments, MAX_SEGMENTS};
+#[cfg(all(feature = "libm", not(any(feature = "std", test))))]
+use crate::math::FloatExt;
+
+pub fn clamp<T: core::cmp::PartialOrd>(value: T, min: T, max: T) -> T {
     if value > max {
         max
     } else if value < min {
@@ -27,21 +34,202 @@ impl Point {
     }
 }
 
-fn approximate_integral(x: f32) -> f32 {
+///
+/// The fourth root is written as two square roots, which unlike `powf` vectorize in the lane
+/// loops of `QuadraticBatch::flatten`
+///
+pub(crate) fn approximate_integral(x: f32) -> f32 {
     const D: f32 = 0.67;
-    x / (1.0 - D + f32::powf(f32::powf(D, 4.0) + 0.25 * x * x, 0.25))
+    const D4: f32 = D * D * D * D;
+
+    x / (1.0 - D + f32::sqrt(f32::sqrt(D4 + 0.25 * x * x)))
 }
 
-fn approximate_inverse_integral(x: f32) -> f32 {
+pub(crate) fn approximate_inverse_integral(x: f32) -> f32 {
     const B: f32 = 0.39;
 
-    x * (1.0 - B + f32::sqrt(B * B - 0.25 * x * x))
+    x * (1.0 - B + f32::sqrt(B * B + 0.25 * x * x))
 }
 
 pub struct ParabolaParams {
-    x0: f32,
-    x2: f32,
-    scale: f32,
+    pub(crate) x0: f32,
+    pub(crate) x2: f32,
+    pub(crate) scale: f32,
+}
+
+///
+/// `ParabolaParams` of `LANES` curves, one array per field
+///
+pub(crate) struct ParabolaLanes<const LANES: usize> {
+    pub(crate) x0: [f32; LANES],
+    pub(crate) x2: [f32; LANES],
+    pub(crate) scale: [f32; LANES],
+}
+
+///
+/// `QuadraticBezier::map_to_basic` on `LANES` curves given as the arrays of their x0, y0, x1,
+/// y1, x2 and y2 coordinates. Every step is a loop over the lanes without branches, which
+/// compiles to vector instructions, only `hypot` stays a call per lane.
+///
+pub(crate) fn map_to_basic_lanes<const LANES: usize>(
+    [x0, y0, x1, y1, x2, y2]: [&[f32; LANES]; 6],
+) -> ParabolaLanes<LANES> {
+    let mut ddx = [0.0_f32; LANES];
+    let mut ddy = [0.0_f32; LANES];
+    let mut cross = [0.0_f32; LANES];
+    let mut params = ParabolaLanes {
+        x0: [0.0; LANES],
+        x2: [0.0; LANES],
+        scale: [0.0; LANES],
+    };
+
+    for lane in 0..LANES {
+        ddx[lane] = 2.0 * x1[lane] - x0[lane] - x2[lane];
+        ddy[lane] = 2.0 * y1[lane] - y0[lane] - y2[lane];
+
+        let u0 = (x1[lane] - x0[lane]) * ddx[lane] + (y1[lane] - y0[lane]) * ddy[lane];
+        let u2 = (x2[lane] - x1[lane]) * ddx[lane] + (y2[lane] - y1[lane]) * ddy[lane];
+
+        cross[lane] = (x2[lane] - x0[lane]) * ddy[lane] - (y2[lane] - y0[lane]) * ddx[lane];
+        params.x0[lane] = u0 / cross[lane];
+        params.x2[lane] = u2 / cross[lane];
+    }
+
+    // Kept apart so that the call does not stop the other loops from being vectorized
+    let norm = core::array::from_fn::<f32, LANES, _>(|lane| f32::hypot(ddx[lane], ddy[lane]));
+
+    for lane in 0..LANES {
+        params.scale[lane] =
+            f32::abs(cross[lane]) / (norm[lane] * f32::abs(params.x2[lane] - params.x0[lane]));
+    }
+
+    params
+}
+
+impl ParabolaParams {
+    ///
+    /// Segment count before rounding up, `a0` and `a2` are the integrals at `x0` and `x2`.
+    /// When the vertex of the parabola is inside the segment, its curvature peak is bounded
+    /// explicitly as in kurbo, the integral alone underestimates it.
+    ///
+    pub(crate) fn segment_count(&self, a0: f32, a2: f32, err: f32) -> f32 {
+        if !self.scale.is_finite() {
+            // Straight line
+            return 0.0;
+        }
+
+        if self.x0.signum() == self.x2.signum() {
+            0.5 * f32::abs(a2 - a0) * f32::sqrt(self.scale / err)
+        } else {
+            let xmin = f32::sqrt(err / self.scale);
+
+            0.5 * f32::abs(a2 - a0) / approximate_integral(xmin)
+        }
+    }
+}
+
+///
+/// 2D affine transform, maps (x, y) to (a * x + c * y + e, b * x + d * y + f) like SVG's `matrix()`
+///
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0)
+    }
+
+    pub fn new(a: f32, b: f32, c: f32, d: f32, e: f32, f: f32) -> Self {
+        Self { a, b, c, d, e, f }
+    }
+
+    pub fn translate(tx: f32, ty: f32) -> Self {
+        Self::new(1.0, 0.0, 0.0, 1.0, tx, ty)
+    }
+
+    pub fn scale(sx: f32, sy: f32) -> Self {
+        Self::new(sx, 0.0, 0.0, sy, 0.0, 0.0)
+    }
+
+    ///
+    /// Angle in radians
+    ///
+    pub fn rotate(angle: f32) -> Self {
+        let (sin, cos) = angle.sin_cos();
+        Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+    }
+
+    pub fn skew_x(angle: f32) -> Self {
+        Self::new(1.0, 0.0, angle.tan(), 1.0, 0.0, 0.0)
+    }
+
+    pub fn skew_y(angle: f32) -> Self {
+        Self::new(1.0, angle.tan(), 0.0, 1.0, 0.0, 0.0)
+    }
+
+    ///
+    /// Returns the transform that applies `other` first and then `self`
+    ///
+    pub fn then(&self, other: &Transform) -> Self {
+        Self {
+            a: self.a * other.a + self.c * other.b,
+            b: self.b * other.a + self.d * other.b,
+            c: self.a * other.c + self.c * other.d,
+            d: self.b * other.c + self.d * other.d,
+            e: self.a * other.e + self.c * other.f + self.e,
+            f: self.b * other.e + self.d * other.f + self.f,
+        }
+    }
+
+    pub fn apply(&self, p: Point) -> Point {
+        Point {
+            x: self.a * p.x + self.c * p.y + self.e,
+            y: self.b * p.x + self.d * p.y + self.f,
+        }
+    }
+
+    pub fn determinant(&self) -> f32 {
+        self.a * self.d - self.b * self.c
+    }
+
+    pub fn invert(&self) -> Option<Self> {
+        let det = self.determinant();
+
+        if det.abs() <= f32::EPSILON * f32::EPSILON {
+            return None;
+        }
+
+        let inv = det.recip();
+
+        Some(Self {
+            a: self.d * inv,
+            b: -self.b * inv,
+            c: -self.c * inv,
+            d: self.a * inv,
+            e: (self.c * self.f - self.d * self.e) * inv,
+            f: (self.b * self.e - self.a * self.f) * inv,
+        })
+    }
+
+    ///
+    /// Average scale factor, used to map lengths like stroke widths
+    ///
+    pub fn mean_scale(&self) -> f32 {
+        self.determinant().abs().sqrt()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -66,6 +254,23 @@ impl QuadraticBezier {
         }
     }
 
+    pub fn points(&self) -> [Point; 3] {
+        [
+            Point {
+                x: self.x0,
+                y: self.y0,
+            },
+            Point {
+                x: self.x1,
+                y: self.y1,
+            },
+            Point {
+                x: self.x2,
+                y: self.y2,
+            },
+        ]
+    }
+
     pub fn eval(&self, t: f32) -> Point {
         let one_minus_t = 1.0 - t;
         let x =
@@ -76,6 +281,12 @@ impl QuadraticBezier {
         Point { x, y }
     }
 
+    pub fn transform(&self, transform: &Transform) -> Self {
+        let [p0, p1, p2] = self.points().map(|p| transform.apply(p));
+
+        Self::new(p0, p1, p2)
+    }
+
     pub fn subsegment(&self, t0: f32, t1: f32) -> Self {
         let (p0, p2) = (self.eval(t0), self.eval(t1));
         let dt = t1 - t0;
@@ -98,7 +309,13 @@ impl QuadraticBezier {
         let y1 = self.y1 - self.y0;
         let x2 = self.x2 - self.x0;
         let y2 = self.y2 - self.y0;
-        let t = (x1 * x2 + y1 * y2) / (x2 * x2 + y2 * y2);
+        let chord_squared = x2 * x2 + y2 * y2;
+        // A closed or zero-length curve has no chord to project on, measure from the start point
+        let t = if chord_squared > 0.0 {
+            (x1 * x2 + y1 * y2) / chord_squared
+        } else {
+            0.0
+        };
         let u = clamp(t, 0.0, 1.0);
         let p = Point {
             x: self.x0,
@@ -121,7 +338,8 @@ impl QuadraticBezier {
     pub fn recursive_subdivide_impl(&self, err: f32, t0: f32, t1: f32, result: &mut Vec<f32>) {
         let q = self.subsegment(t0, t1);
 
-        if q.error() <= err {
+        // Intervals stop halving at 1 / MAX_SEGMENTS, like `DeCasteljauPoints`
+        if q.error() <= err || t1 - t0 <= 1.0 / MAX_SEGMENTS as f32 {
             result.push(t1);
         } else {
             let t_mid = (t0 + t1) * 0.5;
@@ -138,29 +356,33 @@ impl QuadraticBezier {
     }
 
     pub fn map_to_basic(&self) -> ParabolaParams {
-        let ddx = 2.0 * self.x1 - self.x0 - self.x2;
-        let ddy = 2.0 * self.y1 - self.y0 - self.y2;
-        let u0 = (self.x1 - self.x0) * ddx + (self.y1 - self.y0) * ddy;
-        let u2 = (self.x2 - self.x1) * ddx + (self.y2 - self.y1) * ddy;
-        let cross = (self.x2 - self.x0) * ddy - (self.y2 - self.y0) * ddx;
-        let x0 = u0 / cross;
-        let x2 = u2 / cross;
-        let scale = f32::abs(cross) / (f32::hypot(ddx, ddy) * f32::abs(x2 - x0));
+        let lanes = map_to_basic_lanes([
+            &[self.x0],
+            &[self.y0],
+            &[self.x1],
+            &[self.y1],
+            &[self.x2],
+            &[self.y2],
+        ]);
 
-        ParabolaParams { x0, x2, scale }
+        ParabolaParams {
+            x0: lanes.x0[0],
+            x2: lanes.x2[0],
+            scale: lanes.scale[0],
+        }
     }
 
     pub fn smart_subdivide(&self, err: f32) -> Vec<f32> {
         let params = self.map_to_basic();
         let a0 = approximate_integral(params.x0);
         let a2 = approximate_integral(params.x2);
-        let count = 0.5 * f32::abs(a2 - a0) * f32::sqrt(params.scale / err);
-        let n = f32::ceil(count);
+        let segments = bounded_segments(params.segment_count(a0, a2, err));
+        let n = segments as f32;
         let u0 = approximate_inverse_integral(a0);
         let u2 = approximate_inverse_integral(a2);
         let mut result = vec![0_f32];
 
-        for i in 1..(n as i32) {
+        for i in 1..segments {
             let u = approximate_inverse_integral(a0 + ((a2 - a0) * (i as f32)) / n);
             let t = (u - u0) / (u2 - u0);
             result.push(t);
@@ -0,0 +1,203 @@
+use crate::blend::{BlendMode, CompositeOp};
+use crate::flatten::Flattener;
+use crate::geometry::Transform;
+use crate::path::Path;
+use crate::rasterizer::{Blending, Compositor, FillRule, NUM_CHANNELS};
+use crate::{Color, Point, QuadraticBezier, Rasterizer};
+
+///
+/// Physical layout of the color subpixels inside a pixel
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SubpixelOrder {
+    #[default]
+    Rgb,
+    Bgr,
+    ///
+    /// Vertical stripes, red on top
+    ///
+    VRgb,
+    VBgr,
+}
+
+impl SubpixelOrder {
+    fn is_vertical(&self) -> bool {
+        matches!(self, SubpixelOrder::VRgb | SubpixelOrder::VBgr)
+    }
+
+    ///
+    /// Subpixel of the red, green and blue channels
+    ///
+    fn channels(&self) -> [usize; 3] {
+        match self {
+            SubpixelOrder::Rgb | SubpixelOrder::VRgb => [0, 1, 2],
+            SubpixelOrder::Bgr | SubpixelOrder::VBgr => [2, 1, 0],
+        }
+    }
+}
+
+///
+/// FreeType's `FT_LCD_FILTER_DEFAULT` weights
+///
+pub const DEFAULT_LCD_FILTER: [f32; 5] = [
+    8.0 / 256.0,
+    77.0 / 256.0,
+    86.0 / 256.0,
+    77.0 / 256.0,
+    8.0 / 256.0,
+];
+
+///
+/// FreeType's `FT_LCD_FILTER_LIGHT` weights
+///
+pub const LIGHT_LCD_FILTER: [f32; 5] = [0.0, 85.0 / 256.0, 86.0 / 256.0, 85.0 / 256.0, 0.0];
+
+///
+/// Accumulates at 3x the resolution along the subpixel axis, then filters the
+/// coverage across neighbouring subpixels to produce one coverage per channel.
+///
+pub struct LcdRasterizer {
+    pub width: usize,
+    pub height: usize,
+    pub order: SubpixelOrder,
+    ///
+    /// FIR filter applied across subpixels, centered on the middle weight
+    ///
+    pub filter: [f32; 5],
+    pub rasterizer: Rasterizer,
+}
+
+impl LcdRasterizer {
+    ///
+    /// `tolerance` is in pixels, the subpixel space is 3 times finer along one axis
+    ///
+    pub fn new(width: usize, height: usize, tolerance: f32, order: SubpixelOrder) -> Self {
+        let (w, h) = if order.is_vertical() {
+            (width, height * 3)
+        } else {
+            (width * 3, height)
+        };
+
+        Self {
+            width,
+            height,
+            order,
+            filter: DEFAULT_LCD_FILTER,
+            rasterizer: Rasterizer {
+                width: w,
+                height: h,
+                tolerance: tolerance / 3.0,
+                accumulation_buffer: vec![0.0_f32; w * h],
+            },
+        }
+    }
+
+    ///
+    /// Maps pixel space to the subpixel space of the accumulation buffer
+    ///
+    fn subpixel_transform(&self) -> Transform {
+        if self.order.is_vertical() {
+            Transform::scale(1.0, 3.0)
+        } else {
+            Transform::scale(3.0, 1.0)
+        }
+    }
+
+    pub fn draw_line(&mut self, p0: Point, p1: Point) {
+        let transform = self.subpixel_transform();
+        self.rasterizer
+            .draw_line(transform.apply(p0), transform.apply(p1));
+    }
+
+    pub fn draw_quadratic(&mut self, q: QuadraticBezier, method: impl Flattener) {
+        let transform = self.subpixel_transform();
+        self.rasterizer
+            .draw_quadratic(q.transform(&transform), method);
+    }
+
+    pub fn draw_path(&mut self, path: &Path, method: impl Flattener) {
+        let transform = self.subpixel_transform();
+        self.rasterizer
+            .draw_path(&path.transform(&transform), method);
+    }
+
+    ///
+    /// Filtered red, green and blue coverage of every pixel, between 0.0 and 1.0
+    ///
+    pub fn coverage(&self) -> Vec<[f32; 3]> {
+        let (w, h) = (self.rasterizer.width, self.rasterizer.height);
+        let subpixels = self.rasterizer.coverage_mask(FillRule::NonZero);
+
+        // Distance between two neighbouring subpixels in the buffer
+        let (stride, count) = if self.order.is_vertical() {
+            (w, h)
+        } else {
+            (1, w)
+        };
+        let filtered = |index: usize, along: usize| {
+            self.filter
+                .iter()
+                .enumerate()
+                .filter_map(|(k, weight)| {
+                    let offset = along as isize + k as isize - 2;
+
+                    if offset < 0 || offset >= count as isize {
+                        None
+                    } else {
+                        let i = index as isize + (k as isize - 2) * stride as isize;
+                        Some(subpixels[i as usize] * weight)
+                    }
+                })
+                .sum::<f32>()
+                .min(1.0)
+        };
+        let channels = self.order.channels();
+        let mut result = vec![[0.0_f32; 3]; self.width * self.height];
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let mut pixel = [0.0_f32; 3];
+
+                for (c, subpixel) in channels.iter().enumerate() {
+                    pixel[c] = if self.order.is_vertical() {
+                        let sy = y * 3 + subpixel;
+                        filtered(sy * w + x, sy)
+                    } else {
+                        let sx = x * 3 + subpixel;
+                        filtered(y * w + sx, sx)
+                    };
+                }
+
+                result[y * self.width + x] = pixel;
+            }
+        }
+
+        result
+    }
+
+    ///
+    /// Same output as `Rasterizer::render`, each channel composited with its own coverage.
+    /// Alpha is the mean of the three composites.
+    ///
+    pub fn render(&self, fg_color: Color, bg_color: Color) -> Vec<f32> {
+        let compositor = Compositor::new(
+            bg_color,
+            Blending::Srgb,
+            CompositeOp::SrcOver,
+            BlendMode::Normal,
+        );
+        let mut result = vec![0.0_f32; self.width * self.height * NUM_CHANNELS];
+
+        for (i, coverage) in self.coverage().iter().enumerate() {
+            let [r, g, b] = coverage.map(|coverage| compositor.pixel(fg_color, coverage));
+            let buffer_index = i * NUM_CHANNELS;
+
+            result[buffer_index] = r.r;
+            result[buffer_index + 1] = g.g;
+            result[buffer_index + 2] = b.b;
+            result[buffer_index + 3] = (r.a + g.a + b.a) / 3.0;
+        }
+
+        result
+    }
+}
@@ -0,0 +1,46 @@
+// Without std nor libm the build stops at the `compile_error!` below. The crate keeps std in
+// that case, with `math` left out, so that the error is not buried under unresolved imports.
+#![cfg_attr(all(feature = "libm", not(feature = "std")), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("without the std feature, the libm feature is needed for the float functions");
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bench;
+pub mod blend;
+#[cfg(feature = "std")]
+pub mod canvas;
+pub mod color;
+#[cfg(feature = "std")]
+pub mod compare;
+#[cfg(feature = "std")]
+pub mod encode;
+pub mod flatten;
+#[cfg(feature = "font")]
+pub mod font;
+pub mod geometry;
+#[cfg(feature = "std")]
+pub mod lcd;
+#[cfg(all(feature = "libm", not(any(feature = "std", test))))]
+mod math;
+pub mod paint;
+#[cfg(feature = "std")]
+pub mod parallel;
+pub mod path;
+pub mod rasterizer;
+#[cfg(feature = "std")]
+pub mod reference;
+pub mod simd;
+#[cfg(feature = "std")]
+pub mod sparse;
+#[cfg(feature = "std")]
+pub mod svg;
+
+pub use color::{Color, RendererColors};
+pub use flatten::Flattener;
+pub use geometry::{Point, QuadraticBezier};
+pub use rasterizer::{Rasterizer, SubdivisionMethod};
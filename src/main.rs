@@ -1,28 +1,505 @@
-mod color;
-mod font;
-mod geometry;
-mod rasterizer;
+use flattening_quadratic_bezier_curves::bench::{
+    measure, random_quadratics, to_csv, to_json, Measurement,
+};
+use flattening_quadratic_bezier_curves::compare::compare_renders;
+use flattening_quadratic_bezier_curves::encode::{self, Pixels};
+#[cfg(feature = "font")]
+use flattening_quadratic_bezier_curves::font::{font_quadratics, glyph_path, glyph_test};
+use flattening_quadratic_bezier_curves::geometry::Transform;
+#[cfg(feature = "font")]
+use flattening_quadratic_bezier_curves::paint::Paint;
+use flattening_quadratic_bezier_curves::path::Path;
+#[cfg(feature = "font")]
+use flattening_quadratic_bezier_curves::rasterizer::Blending;
+use flattening_quadratic_bezier_curves::rasterizer::{f32_to_u8, FillRule};
+use flattening_quadratic_bezier_curves::reference;
+use flattening_quadratic_bezier_curves::svg::render_svg;
+use flattening_quadratic_bezier_curves::{
+    Color, Point, QuadraticBezier, Rasterizer, RendererColors, SubdivisionMethod,
+};
 
-use color::Color;
-use font::{glyph_test, RendererColors};
-use geometry::{Point, QuadraticBezier};
-use rasterizer::{f32_to_u8, Rasterizer, SubdivisionMethod};
+///
+/// Stand-ins for the glyph sources when the `font` feature is disabled
+///
+#[cfg(not(feature = "font"))]
+mod no_font {
+    use flattening_quadratic_bezier_curves::path::Path;
+    use flattening_quadratic_bezier_curves::QuadraticBezier;
 
-fn render_to(rasterizer: &Rasterizer, name: &str, colors: RendererColors) {
-    let buffer = rasterizer
-        .render(colors.fg_color, colors.bg_color)
+    pub fn font_quadratics(font_path: &str) -> Result<Vec<QuadraticBezier>, String> {
+        Err(format!("{}: fonts need the font feature", font_path))
+    }
+
+    pub fn glyph_path(font_path: &str, _: u16) -> Result<(Path, usize, usize), String> {
+        Err(format!("{}: fonts need the font feature", font_path))
+    }
+}
+
+#[cfg(not(feature = "font"))]
+use no_font::{font_quadratics, glyph_path};
+
+fn save_rgba(pixels: &[f32], width: usize, height: usize, name: &str) {
+    let buffer = pixels
         .iter()
         .map(|value| f32_to_u8(*value))
         .collect::<Vec<u8>>();
 
-    image::save_buffer(
+    save_or_exit(name, Pixels::Rgba8(buffer.as_slice()), width, height);
+}
+
+fn save_or_exit(name: &str, pixels: Pixels, width: usize, height: usize) {
+    if let Err(error) = encode::save(name, pixels, width, height) {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    }
+}
+
+fn render_to(rasterizer: &Rasterizer, name: &str, colors: RendererColors) {
+    save_rgba(
+        rasterizer
+            .render(colors.fg_color, colors.bg_color)
+            .as_slice(),
+        rasterizer.width,
+        rasterizer.height,
         name,
-        buffer.as_slice(),
-        rasterizer.width as u32,
-        rasterizer.height as u32,
-        image::ColorType::Rgba8,
-    )
-    .unwrap();
+    );
+}
+
+#[cfg(feature = "font")]
+fn render_linear_to(rasterizer: &Rasterizer, name: &str, colors: RendererColors) {
+    let pixels = rasterizer.render_with_paint(
+        &Paint::Solid(colors.fg_color),
+        colors.bg_color,
+        Blending::Linear { contrast: 0.0 },
+    );
+
+    save_rgba(
+        pixels.as_slice(),
+        rasterizer.width,
+        rasterizer.height,
+        format!("linear_{}", name).as_str(),
+    );
+}
+
+fn parse_method(name: &str) -> Result<SubdivisionMethod, String> {
+    match name {
+        "recursive" => Ok(SubdivisionMethod::DeCasteljau),
+        "smart" => Ok(SubdivisionMethod::ParabolaApprox),
+        "wang" => Ok(SubdivisionMethod::Wang),
+        "afd" => Ok(SubdivisionMethod::ForwardDifferencing),
+        _ => match name.strip_prefix("uniform:").map(str::parse::<usize>) {
+            Some(Ok(segments)) => Ok(SubdivisionMethod::Uniform { segments }),
+            _ => Err(format!(
+                "Unknown subdivision method '{}', use 'smart', 'recursive', 'wang', 'afd' or 'uniform:<segments>'",
+                name
+            )),
+        },
+    }
+}
+
+fn parse_tolerance(value: &str) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(tolerance) if tolerance.is_finite() && tolerance > 0.0 => Ok(tolerance),
+        _ => Err(format!(
+            "Invalid tolerance '{}', it must be a positive number",
+            value
+        )),
+    }
+}
+
+///
+/// Prints an argument error and exits
+///
+fn exit_with(error: String) -> ! {
+    eprintln!("{}", error);
+    std::process::exit(1);
+}
+
+///
+/// cargo run -- svg <input.svg> <output.png> [tolerance] [smart|recursive|wang|afd|uniform:<n>]
+///
+fn svg_command(args: &[String]) {
+    if args.len() < 2 {
+        eprintln!(
+            "Usage: svg <input.svg> <output.png> [tolerance] [smart|recursive|wang|afd|uniform:<n>]"
+        );
+        std::process::exit(1);
+    }
+
+    let tolerance = args
+        .get(2)
+        .map_or(Ok(0.25), |t| parse_tolerance(t))
+        .unwrap_or_else(|error| exit_with(error));
+    let method = args
+        .get(3)
+        .map_or(Ok(SubdivisionMethod::ParabolaApprox), |m| parse_method(m))
+        .unwrap_or_else(|error| exit_with(error));
+    let source = std::fs::read_to_string(&args[0]).unwrap();
+
+    match render_svg(source.as_str(), tolerance, method) {
+        Ok(canvas) => save_rgba(
+            canvas.to_rgba().as_slice(),
+            canvas.width,
+            canvas.height,
+            &args[1],
+        ),
+        Err(error) => {
+            eprintln!("{}: {}", args[0], error);
+            std::process::exit(1);
+        }
+    }
+}
+
+///
+/// cargo run --release -- bench [output.csv|output.json] [--font <font.ttf>]... [--random <count>]
+///     [--tolerances 0.1,0.25,...] [--methods smart,recursive,...] [--seconds <per run>]
+///
+fn bench_command(args: &[String]) {
+    let mut output = None;
+    let mut fonts = Vec::<String>::new();
+    let mut random = 10000_usize;
+    let mut tolerances = vec![0.1_f32, 0.25, 0.5, 1.0];
+    let mut methods = vec![
+        SubdivisionMethod::ParabolaApprox,
+        SubdivisionMethod::DeCasteljau,
+        SubdivisionMethod::Wang,
+        SubdivisionMethod::ForwardDifferencing,
+    ];
+    let mut seconds = 0.2_f32;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || match args.next() {
+            Some(value) => value.as_str(),
+            None => {
+                eprintln!("Missing value after {}", arg);
+                std::process::exit(1);
+            }
+        };
+
+        match arg.as_str() {
+            "--font" => fonts.push(value().to_string()),
+            "--random" => random = value().parse().unwrap(),
+            "--tolerances" => {
+                tolerances = value()
+                    .split(',')
+                    .map(|t| parse_tolerance(t).unwrap_or_else(|error| exit_with(error)))
+                    .collect()
+            }
+            "--methods" => {
+                methods = value()
+                    .split(',')
+                    .map(|m| parse_method(m).unwrap_or_else(|error| exit_with(error)))
+                    .collect()
+            }
+            "--seconds" => seconds = value().parse().unwrap(),
+            _ => output = Some(arg.clone()),
+        }
+    }
+
+    #[cfg(feature = "font")]
+    if fonts.is_empty() {
+        fonts.push("media/Roboto-MediumItalic.ttf".to_string());
+        fonts.push("media/Jfwildwood-ldYZ.ttf".to_string());
+    }
+
+    let mut corpora = Vec::<(String, Vec<QuadraticBezier>)>::new();
+
+    for font in fonts.iter() {
+        match font_quadratics(font) {
+            Ok(curves) => corpora.push((font.clone(), curves)),
+            Err(error) => {
+                eprintln!("{}", error);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if random > 0 {
+        corpora.push((
+            format!("random_{}", random),
+            random_quadratics(random, 1, 1000.0),
+        ));
+    }
+
+    let min_duration = std::time::Duration::from_secs_f32(seconds);
+    let mut measurements = Vec::<Measurement>::new();
+
+    println!(
+        "{:<32} {:<10} {:>9} {:>12} {:>12} {:>10} {:>10}",
+        "corpus", "method", "tolerance", "ns/curve", "segments", "max err", "mean err"
+    );
+
+    for (name, curves) in corpora.iter() {
+        for tolerance in tolerances.iter() {
+            for method in methods.iter() {
+                let m = measure(
+                    name,
+                    method.name(),
+                    *method,
+                    curves,
+                    *tolerance,
+                    min_duration,
+                );
+
+                println!(
+                    "{:<32} {:<10} {:>9} {:>12.1} {:>12} {:>10.4} {:>10.4}",
+                    m.corpus,
+                    m.method,
+                    m.tolerance,
+                    m.nanos_per_curve,
+                    m.segments,
+                    m.max_error,
+                    m.mean_error
+                );
+                measurements.push(m);
+            }
+        }
+    }
+
+    if let Some(output) = output {
+        let contents = if output.ends_with(".json") {
+            to_json(measurements.as_slice())
+        } else {
+            to_csv(measurements.as_slice())
+        };
+
+        std::fs::write(&output, contents).unwrap();
+    }
+}
+
+///
+/// How `compare` and `area` draw the curves
+///
+#[derive(Debug, Clone, Copy)]
+enum Rendering {
+    Flattened(SubdivisionMethod),
+    ///
+    /// `Rasterizer::draw_path_exact`, the tolerance only applies to cubics
+    ///
+    Exact,
+    ///
+    /// `reference::supersample` with that many samples per side of each pixel
+    ///
+    Reference(usize),
+}
+
+impl Rendering {
+    fn name(&self) -> String {
+        match self {
+            Rendering::Flattened(method) => method.name().to_string(),
+            Rendering::Exact => "exact".to_string(),
+            Rendering::Reference(samples) => format!("reference:{}", samples),
+        }
+    }
+}
+
+///
+/// `smart` or `smart@0.5`, the tolerance defaults to 0.25. Besides the methods of
+/// `parse_method`, `exact` and `reference:<samples>` render without flattening.
+///
+fn parse_rendering(arg: &str) -> (Rendering, f32) {
+    let (method, tolerance) = match arg.split_once('@') {
+        Some((method, tolerance)) => (
+            method,
+            parse_tolerance(tolerance).unwrap_or_else(|error| exit_with(error)),
+        ),
+        None => (arg, 0.25),
+    };
+    let rendering = match method {
+        "exact" => Rendering::Exact,
+        _ => match method.strip_prefix("reference:").map(str::parse::<usize>) {
+            Some(Ok(samples)) => Rendering::Reference(samples),
+            _ => {
+                Rendering::Flattened(parse_method(method).unwrap_or_else(|error| exit_with(error)))
+            }
+        },
+    };
+
+    (rendering, tolerance)
+}
+
+///
+/// Glyph `font.ttf:<glyph index>` on the canvas of `rasterize_glyph`, or SVG path data on a
+/// canvas fitted around it with a pixel of margin
+///
+fn parse_source(source: &str) -> Result<(Path, usize, usize), String> {
+    match source.rsplit_once(':') {
+        Some((font, glyph)) if font.ends_with(".ttf") || font.ends_with(".otf") => {
+            let glyph = glyph.parse::<u16>().map_err(|error| error.to_string())?;
+
+            glyph_path(font, glyph)
+        }
+        _ => {
+            let path = Path::from_svg(source)?;
+            let (min, max) = path.bounds().ok_or("Empty path")?;
+            let path = path.transform(&Transform::translate(1.0 - min.x, 1.0 - min.y));
+            let width = (max.x - min.x).ceil() as usize + 2;
+            let height = (max.y - min.y).ceil() as usize + 2;
+
+            Ok((path, width, height))
+        }
+    }
+}
+
+fn rasterize(
+    (path, width, height): &(Path, usize, usize),
+    rendering: Rendering,
+    tolerance: f32,
+) -> Rasterizer {
+    let (width, height) = (*width, *height);
+
+    if let Rendering::Reference(samples) = rendering {
+        return reference::to_rasterizer(
+            reference::supersample(path, width, height, samples).as_slice(),
+            width,
+            height,
+        );
+    }
+
+    let mut rasterizer = Rasterizer {
+        width,
+        height,
+        tolerance,
+        accumulation_buffer: vec![0.0_f32; width * height],
+    };
+
+    match rendering {
+        Rendering::Flattened(method) => rasterizer.draw_path(path, method),
+        _ => rasterizer.draw_path_exact(path),
+    }
+    rasterizer
+}
+
+fn parse_source_or_exit(source: &str) -> (Path, usize, usize) {
+    parse_source(source).unwrap_or_else(|error| {
+        eprintln!("{}", error);
+        std::process::exit(1);
+    })
+}
+
+///
+/// cargo run --release -- compare <font.ttf:glyph index | svg path data> <method>[@tolerance]
+///     <method>[@tolerance] <heatmap.png> [amplification]
+///
+/// `exact` and `reference:<samples>` as methods render without flattening, as the ground
+/// truth for the others.
+///
+fn compare_command(args: &[String]) {
+    if args.len() < 4 {
+        eprintln!(
+            "Usage: compare <font.ttf:glyph index | svg path data> <method>[@tolerance] \
+             <method>[@tolerance] <heatmap.png> [amplification]"
+        );
+        std::process::exit(1);
+    }
+
+    let source = parse_source_or_exit(&args[0]);
+    let amplify = args.get(4).map_or(16.0, |a| a.parse::<f32>().unwrap());
+    let (rendering_a, tolerance_a) = parse_rendering(&args[1]);
+    let (rendering_b, tolerance_b) = parse_rendering(&args[2]);
+    let a = rasterize(&source, rendering_a, tolerance_a);
+    let b = rasterize(&source, rendering_b, tolerance_b);
+    let (fg, bg) = (Color::black(), Color::white());
+    let diff = compare_renders(
+        a.render(fg, bg).as_slice(),
+        b.render(fg, bg).as_slice(),
+        a.width,
+        a.height,
+        amplify,
+    );
+
+    println!("PSNR: {:.2} dB", diff.psnr);
+    println!(
+        "Max absolute difference: {:.4} ({:.1}/255)",
+        diff.max_difference,
+        diff.max_difference * 255.0
+    );
+    println!(
+        "Differing pixels: {} of {}",
+        diff.differing_pixels,
+        diff.width * diff.height
+    );
+
+    save_or_exit(
+        &args[3],
+        Pixels::Rgba8(diff.heatmap.as_slice()),
+        diff.width,
+        diff.height,
+    );
+}
+
+///
+/// cargo run --release -- mask <font.ttf:glyph index | svg path data> <output>
+///     [<method>[@tolerance]] [--evenodd] [--16]
+///
+fn mask_command(args: &[String]) {
+    if args.len() < 2 {
+        eprintln!(
+            "Usage: mask <font.ttf:glyph index | svg path data> <output> \
+             [<method>[@tolerance]] [--evenodd] [--16]"
+        );
+        std::process::exit(1);
+    }
+
+    let source = parse_source_or_exit(&args[0]);
+    let options = &args[2..];
+    let (rendering, tolerance) = options.iter().find(|arg| !arg.starts_with("--")).map_or(
+        (
+            Rendering::Flattened(SubdivisionMethod::ParabolaApprox),
+            0.25,
+        ),
+        |arg| parse_rendering(arg),
+    );
+    let fill_rule = if options.iter().any(|arg| arg == "--evenodd") {
+        FillRule::EvenOdd
+    } else {
+        FillRule::NonZero
+    };
+    let rasterizer = rasterize(&source, rendering, tolerance);
+    let (width, height) = (rasterizer.width, rasterizer.height);
+
+    if options.iter().any(|arg| arg == "--16") {
+        let mask = rasterizer.coverage_mask_u16(fill_rule);
+        save_or_exit(&args[1], Pixels::Alpha16(mask.as_slice()), width, height);
+    } else {
+        let mask = rasterizer.coverage_mask_u8(fill_rule);
+        save_or_exit(&args[1], Pixels::Alpha8(mask.as_slice()), width, height);
+    }
+}
+
+///
+/// cargo run --release -- area <font.ttf:glyph index | svg path data> [<method>[@tolerance]]...
+///
+fn area_command(args: &[String]) {
+    if args.is_empty() {
+        eprintln!("Usage: area <font.ttf:glyph index | svg path data> [<method>[@tolerance]]...");
+        std::process::exit(1);
+    }
+
+    let source = parse_source_or_exit(&args[0]);
+    let exact = source.0.signed_area();
+    let methods = if args.len() > 1 {
+        args[1..].to_vec()
+    } else {
+        vec!["smart".to_string(), "recursive".to_string()]
+    };
+
+    println!("Analytic signed area: {}", exact);
+
+    for arg in methods {
+        let (rendering, tolerance) = parse_rendering(&arg);
+        let accumulated = rasterize(&source, rendering, tolerance).signed_area();
+
+        println!(
+            "{}@{}: accumulated {}, difference {:+} ({:+.4}%)",
+            rendering.name(),
+            tolerance,
+            accumulated,
+            accumulated - exact,
+            100.0 * (accumulated - exact) / exact.abs().max(f32::MIN_POSITIVE)
+        );
+    }
 }
 
 fn simple_output_comparison_test() {
@@ -102,6 +579,7 @@ fn simple_quadratic_curve_image_test() {
     }
 }
 
+#[cfg(feature = "font")]
 struct GlyphTestDesc<'a> {
     font_path: &'a str,
     glyph_index: u16,
@@ -110,6 +588,41 @@ struct GlyphTestDesc<'a> {
 }
 
 fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+
+    match args.get(1).map(String::as_str) {
+        Some("svg") => {
+            svg_command(&args[2..]);
+            return;
+        }
+        Some("bench") => {
+            bench_command(&args[2..]);
+            return;
+        }
+        Some("compare") => {
+            compare_command(&args[2..]);
+            return;
+        }
+        Some("area") => {
+            area_command(&args[2..]);
+            return;
+        }
+        Some("mask") => {
+            mask_command(&args[2..]);
+            return;
+        }
+        _ => {}
+    }
+
+    simple_output_comparison_test();
+    simple_quadratic_curve_image_test();
+
+    #[cfg(feature = "font")]
+    glyph_image_tests();
+}
+
+#[cfg(feature = "font")]
+fn glyph_image_tests() {
     let glyph_test_data = [
         GlyphTestDesc {
             font_path: "media/Roboto-MediumItalic.ttf",
@@ -140,10 +653,7 @@ fn main() {
         },
     ];
 
-    simple_output_comparison_test();
-    simple_quadratic_curve_image_test();
-
-    for test in glyph_test_data {
+    for test in glyph_test_data.iter() {
         glyph_test(
             test.font_path,
             test.glyph_index,
@@ -152,4 +662,14 @@ fn main() {
             render_to,
         );
     }
+
+    // Light on dark is where blending in sRGB hurts the most
+    let light_on_dark = &glyph_test_data[2];
+    glyph_test(
+        light_on_dark.font_path,
+        light_on_dark.glyph_index,
+        light_on_dark.tolerance,
+        light_on_dark.colors,
+        render_linear_to,
+    );
 }
@@ -0,0 +1,85 @@
+//!
+//! Float functions that `core` lacks, taken from libm when the `std` feature is disabled.
+//! Modules import `FloatExt` only without std, so that the inherent methods keep being used
+//! otherwise and the call sites stay the same in both builds. Test builds link std, which
+//! brings the inherent methods back, so they don't need it either.
+//!
+
+pub(crate) trait FloatExt {
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn sqrt(self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn powf(self, n: Self) -> Self;
+    fn exp2(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn sin_cos(self) -> (Self, Self)
+    where
+        Self: Sized;
+    fn acos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn rem_euclid(self, rhs: Self) -> Self;
+}
+
+impl FloatExt for f32 {
+    fn floor(self) -> Self {
+        libm::floorf(self)
+    }
+
+    fn ceil(self) -> Self {
+        libm::ceilf(self)
+    }
+
+    fn sqrt(self) -> Self {
+        libm::sqrtf(self)
+    }
+
+    fn hypot(self, other: Self) -> Self {
+        libm::hypotf(self, other)
+    }
+
+    fn powf(self, n: Self) -> Self {
+        libm::powf(self, n)
+    }
+
+    fn exp2(self) -> Self {
+        libm::exp2f(self)
+    }
+
+    fn sin(self) -> Self {
+        libm::sinf(self)
+    }
+
+    fn cos(self) -> Self {
+        libm::cosf(self)
+    }
+
+    fn tan(self) -> Self {
+        libm::tanf(self)
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        libm::sincosf(self)
+    }
+
+    fn acos(self) -> Self {
+        libm::acosf(self)
+    }
+
+    fn atan2(self, other: Self) -> Self {
+        libm::atan2f(self, other)
+    }
+
+    fn rem_euclid(self, rhs: Self) -> Self {
+        // Same as std
+        let r = self % rhs;
+
+        if r < 0.0 {
+            r + rhs.abs()
+        } else {
+            r
+        }
+    }
+}
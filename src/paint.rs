@@ -0,0 +1,509 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::geometry::Transform;
+#[cfg(all(feature = "libm", not(any(feature = "std", test))))]
+use crate::math::FloatExt;
+use crate::{Color, Point};
+
+///
+/// How a gradient or pattern is continued outside of its bounds
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Spread {
+    #[default]
+    Pad,
+    Repeat,
+    Reflect,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ColorStop {
+    pub offset: f32,
+    pub color: Color,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum GradientKind {
+    Linear {
+        start: Point,
+        end: Point,
+    },
+    ///
+    /// Focal point is clamped inside the circle, as in SVG 1.1
+    ///
+    Radial {
+        center: Point,
+        radius: f32,
+        focal: Point,
+    },
+    ///
+    /// Angles in radians, measured clockwise from the positive x axis in a y-down space
+    ///
+    Sweep {
+        center: Point,
+        start_angle: f32,
+        end_angle: f32,
+    },
+}
+
+///
+/// Stops must be sorted by offset. The transform maps gradient space to canvas space, it is
+/// kept along with its inverse so sampling doesn't invert it for every pixel.
+///
+#[derive(Debug, Clone)]
+pub struct Gradient {
+    pub kind: GradientKind,
+    pub stops: Vec<ColorStop>,
+    pub spread: Spread,
+    transform: Transform,
+    inverse: Option<Transform>,
+}
+
+///
+/// How an image pattern is sampled between texels
+///
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    #[default]
+    Bilinear,
+    ///
+    /// Catmull-Rom spline over the 4x4 nearest texels
+    ///
+    Bicubic,
+}
+
+///
+/// Image paint, the transform maps image space (one unit per texel) to canvas space and is kept
+/// along with its inverse. `spread` decides what lies outside of the image: `Pad` clamps to the
+/// edge texels.
+///
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub width: usize,
+    pub height: usize,
+    ///
+    /// RGBA with premultiplied alpha, components between 0.0 and 1.0
+    ///
+    pub pixels: Vec<f32>,
+    transform: Transform,
+    inverse: Option<Transform>,
+    pub spread: Spread,
+    pub filter: Filter,
+    ///
+    /// Multiplies the alpha of every sample
+    ///
+    pub opacity: f32,
+}
+
+#[derive(Debug, Clone)]
+pub enum Paint {
+    Solid(Color),
+    Gradient(Gradient),
+    Pattern(Pattern),
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::Solid(color)
+    }
+}
+
+impl Paint {
+    ///
+    /// Returns the same paint with its alpha multiplied by `opacity`
+    ///
+    pub fn with_opacity(mut self, opacity: f32) -> Self {
+        match &mut self {
+            Paint::Solid(color) => color.a *= opacity,
+            Paint::Gradient(gradient) => gradient
+                .stops
+                .iter_mut()
+                .for_each(|stop| stop.color.a *= opacity),
+            Paint::Pattern(pattern) => pattern.opacity *= opacity,
+        }
+
+        self
+    }
+
+    ///
+    /// Color of the paint at canvas position (x, y)
+    ///
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        match self {
+            Paint::Solid(color) => *color,
+            Paint::Gradient(gradient) => gradient.color_at(x, y),
+            Paint::Pattern(pattern) => pattern.color_at(x, y),
+        }
+    }
+}
+
+impl Gradient {
+    pub fn new(
+        kind: GradientKind,
+        stops: Vec<ColorStop>,
+        spread: Spread,
+        transform: Transform,
+    ) -> Self {
+        Self {
+            kind,
+            stops,
+            spread,
+            transform,
+            inverse: transform.invert(),
+        }
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        self.inverse = transform.invert();
+    }
+
+    ///
+    /// Transparent everywhere when the transform is singular and collapses the gradient
+    ///
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        let offset = self
+            .inverse
+            .and_then(|inverse| self.offset(inverse.apply(Point { x, y })));
+
+        match offset {
+            Some(t) => self.color_at_offset(self.spread.apply(t)),
+            None => Color {
+                a: 0.0,
+                ..Color::black()
+            },
+        }
+    }
+
+    ///
+    /// Gradient parameter before applying the spread, `None` when it is undefined
+    ///
+    fn offset(&self, p: Point) -> Option<f32> {
+        match self.kind {
+            GradientKind::Linear { start, end } => {
+                let (dx, dy) = (end.x - start.x, end.y - start.y);
+                let length_squared = dx * dx + dy * dy;
+
+                if length_squared <= 0.0 {
+                    return None;
+                }
+
+                Some(((p.x - start.x) * dx + (p.y - start.y) * dy) / length_squared)
+            }
+            GradientKind::Radial {
+                center,
+                radius,
+                focal,
+            } => {
+                if radius <= 0.0 {
+                    return None;
+                }
+
+                // Keep the focal point strictly inside the circle
+                let mut focal = focal;
+                let (fx, fy) = (focal.x - center.x, focal.y - center.y);
+                let focal_distance = f32::hypot(fx, fy);
+                let max_distance = 0.99 * radius;
+
+                if focal_distance > max_distance {
+                    let scale = max_distance / focal_distance;
+                    focal = Point {
+                        x: center.x + fx * scale,
+                        y: center.y + fy * scale,
+                    };
+                }
+
+                // Solve |q - t * d| = t * radius for the circle going through p
+                let (dx, dy) = (center.x - focal.x, center.y - focal.y);
+                let (qx, qy) = (p.x - focal.x, p.y - focal.y);
+                let a = dx * dx + dy * dy - radius * radius;
+                let b = qx * dx + qy * dy;
+                let c = qx * qx + qy * qy;
+                let discriminant = f32::max(b * b - a * c, 0.0);
+
+                Some((b - discriminant.sqrt()) / a)
+            }
+            GradientKind::Sweep {
+                center,
+                start_angle,
+                end_angle,
+            } => {
+                let span = end_angle - start_angle;
+
+                if span.abs() <= f32::EPSILON {
+                    return None;
+                }
+
+                let two_pi = 2.0 * core::f32::consts::PI;
+                let angle = f32::atan2(p.y - center.y, p.x - center.x);
+                let angle = (angle - start_angle).rem_euclid(two_pi);
+
+                Some(angle / span)
+            }
+        }
+    }
+
+    ///
+    /// Interpolates the stops in straight alpha, `t` must be in [0, 1]
+    ///
+    fn color_at_offset(&self, t: f32) -> Color {
+        let stops = self.stops.as_slice();
+
+        match stops {
+            [] => Color {
+                a: 0.0,
+                ..Color::black()
+            },
+            [only] => only.color,
+            _ => {
+                if t <= stops[0].offset {
+                    return stops[0].color;
+                }
+
+                for pair in stops.windows(2) {
+                    let (s0, s1) = (pair[0], pair[1]);
+
+                    if t <= s1.offset {
+                        let span = s1.offset - s0.offset;
+                        let u = if span > 0.0 {
+                            (t - s0.offset) / span
+                        } else {
+                            1.0
+                        };
+
+                        return Color {
+                            r: s0.color.r + (s1.color.r - s0.color.r) * u,
+                            g: s0.color.g + (s1.color.g - s0.color.g) * u,
+                            b: s0.color.b + (s1.color.b - s0.color.b) * u,
+                            a: s0.color.a + (s1.color.a - s0.color.a) * u,
+                        };
+                    }
+                }
+
+                stops[stops.len() - 1].color
+            }
+        }
+    }
+}
+
+impl Spread {
+    ///
+    /// Maps a texel index into [0, size)
+    ///
+    fn wrap(&self, i: i64, size: usize) -> usize {
+        let size = size as i64;
+
+        let wrapped = match self {
+            Spread::Pad => i.clamp(0, size - 1),
+            Spread::Repeat => i.rem_euclid(size),
+            Spread::Reflect => {
+                let i = i.rem_euclid(2 * size);
+
+                if i >= size {
+                    2 * size - 1 - i
+                } else {
+                    i
+                }
+            }
+        };
+
+        wrapped as usize
+    }
+
+    pub fn apply(&self, t: f32) -> f32 {
+        match self {
+            Spread::Pad => t.clamp(0.0, 1.0),
+            Spread::Repeat => t.rem_euclid(1.0),
+            Spread::Reflect => {
+                let t = t.rem_euclid(2.0);
+
+                if t > 1.0 {
+                    2.0 - t
+                } else {
+                    t
+                }
+            }
+        }
+    }
+}
+
+impl Pattern {
+    ///
+    /// Creates a pattern from straight alpha RGBA8 data, using the identity transform.
+    /// `data` must hold exactly `width * height` pixels.
+    ///
+    pub fn from_rgba8(width: usize, height: usize, data: &[u8]) -> Result<Self, String> {
+        let expected = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or_else(|| format!("Pattern size {}x{} overflows", width, height))?;
+
+        if data.len() != expected {
+            return Err(format!(
+                "Pattern data of {} bytes for {}x{} RGBA8 pixels, expected {}",
+                data.len(),
+                width,
+                height,
+                expected
+            ));
+        }
+
+        let mut pixels = data
+            .iter()
+            .map(|value| *value as f32 / 255.0)
+            .collect::<Vec<f32>>();
+
+        pixels.chunks_mut(4).for_each(|chunk| {
+            chunk[0] *= chunk[3];
+            chunk[1] *= chunk[3];
+            chunk[2] *= chunk[3];
+        });
+
+        Ok(Self {
+            width,
+            height,
+            pixels,
+            transform: Transform::identity(),
+            inverse: Some(Transform::identity()),
+            spread: Spread::Repeat,
+            filter: Filter::Bilinear,
+            opacity: 1.0,
+        })
+    }
+
+    ///
+    /// Loads any format supported by the `image` crate
+    ///
+    #[cfg(feature = "image")]
+    pub fn from_file(path: &str) -> Result<Self, String> {
+        let image = image::open(path)
+            .map_err(|error| format!("{}: {}", path, error))?
+            .to_rgba8();
+        let (width, height) = image.dimensions();
+
+        Self::from_rgba8(width as usize, height as usize, image.as_raw().as_slice())
+    }
+
+    pub fn transform(&self) -> Transform {
+        self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Transform) {
+        self.transform = transform;
+        self.inverse = transform.invert();
+    }
+
+    ///
+    /// Transparent everywhere when the transform is singular and collapses the image
+    ///
+    pub fn color_at(&self, x: f32, y: f32) -> Color {
+        let p = match self.inverse {
+            Some(inverse) => inverse.apply(Point { x, y }),
+            None => {
+                return Color {
+                    a: 0.0,
+                    ..Color::black()
+                }
+            }
+        };
+        // Texel centers are at half integer coordinates
+        let (u, v) = (p.x - 0.5, p.y - 0.5);
+
+        let sample = match self.filter {
+            Filter::Nearest => self.texel(p.x.floor() as i64, p.y.floor() as i64),
+            Filter::Bilinear => {
+                let (x0, y0) = (u.floor(), v.floor());
+                let (fx, fy) = (u - x0, v - y0);
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let mut result = [0.0_f32; 4];
+
+                for (dy, wy) in [(0, 1.0 - fy), (1, fy)] {
+                    for (dx, wx) in [(0, 1.0 - fx), (1, fx)] {
+                        let texel = self.texel(x0 + dx, y0 + dy);
+
+                        for c in 0..4 {
+                            result[c] += texel[c] * wx * wy;
+                        }
+                    }
+                }
+
+                result
+            }
+            Filter::Bicubic => {
+                let (x0, y0) = (u.floor(), v.floor());
+                let (wxs, wys) = (catmull_rom_weights(u - x0), catmull_rom_weights(v - y0));
+                let (x0, y0) = (x0 as i64, y0 as i64);
+                let mut result = [0.0_f32; 4];
+
+                for (j, wy) in wys.iter().enumerate() {
+                    for (i, wx) in wxs.iter().enumerate() {
+                        let texel = self.texel(x0 + i as i64 - 1, y0 + j as i64 - 1);
+
+                        for c in 0..4 {
+                            result[c] += texel[c] * wx * wy;
+                        }
+                    }
+                }
+
+                // The spline overshoots, keep the result a valid premultiplied color
+                let a = result[3].clamp(0.0, 1.0);
+                [
+                    result[0].clamp(0.0, a),
+                    result[1].clamp(0.0, a),
+                    result[2].clamp(0.0, a),
+                    a,
+                ]
+            }
+        };
+
+        if sample[3] <= 0.0 {
+            return Color {
+                a: 0.0,
+                ..Color::black()
+            };
+        }
+
+        Color {
+            r: sample[0] / sample[3],
+            g: sample[1] / sample[3],
+            b: sample[2] / sample[3],
+            a: sample[3] * self.opacity,
+        }
+    }
+
+    fn texel(&self, x: i64, y: i64) -> [f32; 4] {
+        if self.width == 0 || self.height == 0 {
+            return [0.0; 4];
+        }
+
+        let x = self.spread.wrap(x, self.width);
+        let y = self.spread.wrap(y, self.height);
+        let i = (y * self.width + x) * 4;
+
+        [
+            self.pixels[i],
+            self.pixels[i + 1],
+            self.pixels[i + 2],
+            self.pixels[i + 3],
+        ]
+    }
+}
+
+fn catmull_rom_weights(t: f32) -> [f32; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    [
+        0.5 * (-t3 + 2.0 * t2 - t),
+        0.5 * (3.0 * t3 - 5.0 * t2 + 2.0),
+        0.5 * (-3.0 * t3 + 4.0 * t2 + t),
+        0.5 * (t3 - t2),
+    ]
+}
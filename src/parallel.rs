@@ -0,0 +1,155 @@
+use crate::blend::{BlendMode, CompositeOp};
+use crate::flatten::{ClosingSink, Flattener};
+use crate::paint::Paint;
+use crate::path::Path;
+use crate::rasterizer::{accumulate_line, clip_line, Blending, Compositor, NUM_CHANNELS};
+use crate::{Color, Point, Rasterizer};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+///
+/// Runs `work` on every band, with rayon when the `rayon` feature is enabled
+/// and with one scoped thread per band otherwise
+///
+fn for_each_band<T: Send>(bands: Vec<T>, work: impl Fn(T) + Sync + Send) {
+    #[cfg(feature = "rayon")]
+    bands.into_par_iter().for_each(work);
+
+    #[cfg(not(feature = "rayon"))]
+    std::thread::scope(|scope| {
+        let work = &work;
+
+        for band in bands {
+            scope.spawn(move || work(band));
+        }
+    });
+}
+
+impl Rasterizer {
+    ///
+    /// Rows per band. There are never more bands than rows, and without rayon never more than
+    /// the threads the machine runs at once, since every band gets its own thread.
+    ///
+    fn band_height(&self, bands: usize) -> usize {
+        let bands = bands.clamp(1, self.height.max(1));
+        #[cfg(not(feature = "rayon"))]
+        let bands = bands.min(std::thread::available_parallelism().map_or(1, |n| n.get()));
+
+        usize::max(1, self.height.div_ceil(bands))
+    }
+
+    ///
+    /// Same result as calling `draw_line` on every line in order, bit for bit. The canvas is split
+    /// into `bands` row bands, every band accumulates the lines that touch it on its own thread.
+    ///
+    pub fn draw_lines_parallel(&mut self, lines: &[(Point, Point)], bands: usize) {
+        let (width, height) = (self.width, self.height);
+
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let band_height = self.band_height(bands);
+        let band_count = height.div_ceil(band_height);
+        let mut bins = vec![Vec::<usize>::new(); band_count];
+
+        for (i, (p0, p1)) in lines.iter().enumerate() {
+            let (min_y, max_y) = (p0.y.min(p1.y), p0.y.max(p1.y));
+
+            if !(min_y.is_finite() && max_y.is_finite()) || max_y <= 0.0 || min_y >= height as f32 {
+                continue;
+            }
+
+            let first = (min_y.max(0.0) as usize).min(height - 1) / band_height;
+            let last = (max_y.ceil() as usize).min(height - 1) / band_height;
+
+            for bin in bins[first..=last].iter_mut() {
+                bin.push(i);
+            }
+        }
+
+        let bands = self
+            .accumulation_buffer
+            .chunks_mut(band_height * width)
+            .zip(bins.iter())
+            .enumerate()
+            .collect::<Vec<_>>();
+
+        for_each_band(bands, |(band, (buffer, bin))| {
+            let rows = band * band_height..usize::min(height, (band + 1) * band_height);
+
+            for &i in bin.iter() {
+                let (p0, p1) = lines[i];
+
+                clip_line(width, height, p0, p1, |a, b| {
+                    accumulate_line(buffer, width, rows.clone(), a, b);
+                });
+            }
+        });
+    }
+
+    ///
+    /// Parallel version of `draw_path`
+    ///
+    pub fn draw_path_parallel(&mut self, path: &Path, method: impl Flattener, bands: usize) {
+        let mut lines = Vec::<(Point, Point)>::new();
+
+        path.flatten_into(self.tolerance, method, &mut ClosingSink::new(&mut lines));
+
+        self.draw_lines_parallel(lines.as_slice(), bands);
+    }
+
+    ///
+    /// Parallel version of `render_with_paint`, every band of rows is resolved on its own thread
+    ///
+    pub fn render_parallel(
+        &self,
+        paint: &Paint,
+        bg_color: Color,
+        blending: Blending,
+        bands: usize,
+    ) -> Vec<f32> {
+        self.render_parallel_composited(
+            paint,
+            bg_color,
+            blending,
+            CompositeOp::SrcOver,
+            BlendMode::Normal,
+            bands,
+        )
+    }
+
+    ///
+    /// Parallel version of `render_composited`
+    ///
+    pub fn render_parallel_composited(
+        &self,
+        paint: &Paint,
+        bg_color: Color,
+        blending: Blending,
+        composite_op: CompositeOp,
+        blend_mode: BlendMode,
+        bands: usize,
+    ) -> Vec<f32> {
+        let mut result = vec![0.0_f32; self.width * self.height * NUM_CHANNELS];
+
+        if result.is_empty() {
+            return result;
+        }
+
+        let compositor = Compositor::new(bg_color, blending, composite_op, blend_mode);
+        let band_height = self.band_height(bands);
+        let chunks = result
+            .chunks_mut(band_height * self.width * NUM_CHANNELS)
+            .enumerate()
+            .collect::<Vec<_>>();
+
+        for_each_band(chunks, |(band, chunk)| {
+            let rows = band * band_height..usize::min(self.height, (band + 1) * band_height);
+            self.render_rows(paint, &compositor, rows, chunk);
+        });
+
+        result
+    }
+}
@@ -0,0 +1,747 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::flatten::{bounded_segments, Flattener, PathSink};
+use crate::geometry::{Point, QuadraticBezier, Transform};
+#[cfg(all(feature = "libm", not(any(feature = "std", test))))]
+use crate::math::FloatExt;
+
+#[derive(Debug, Clone, Copy)]
+pub enum PathCommand {
+    MoveTo(Point),
+    LineTo(Point),
+    QuadTo(Point, Point),
+    CubicTo(Point, Point, Point),
+    Close,
+}
+
+///
+/// Sequence of subpaths, each one starting with a `MoveTo`
+///
+#[derive(Debug, Clone, Default)]
+pub struct Path {
+    pub commands: Vec<PathCommand>,
+}
+
+///
+/// A flattened subpath
+///
+#[derive(Debug, Clone)]
+pub struct Polyline {
+    pub points: Vec<Point>,
+    pub closed: bool,
+}
+
+///
+/// Gathers the subpaths sent by `Path::flatten_into` into polylines
+///
+struct PolylineCollector {
+    result: Vec<Polyline>,
+    current: Vec<Point>,
+}
+
+impl PathSink for PolylineCollector {
+    fn move_to(&mut self, p: Point) {
+        self.current.push(p);
+    }
+
+    fn line_to(&mut self, p: Point) {
+        self.current.push(p);
+    }
+
+    fn end(&mut self, closed: bool) {
+        self.result.push(Polyline {
+            points: core::mem::take(&mut self.current),
+            closed,
+        });
+    }
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn move_to(&mut self, p: Point) {
+        self.commands.push(PathCommand::MoveTo(p));
+    }
+
+    pub fn line_to(&mut self, p: Point) {
+        self.commands.push(PathCommand::LineTo(p));
+    }
+
+    pub fn quad_to(&mut self, p1: Point, p: Point) {
+        self.commands.push(PathCommand::QuadTo(p1, p));
+    }
+
+    pub fn cubic_to(&mut self, p1: Point, p2: Point, p: Point) {
+        self.commands.push(PathCommand::CubicTo(p1, p2, p));
+    }
+
+    pub fn close(&mut self) {
+        self.commands.push(PathCommand::Close);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    ///
+    /// Smallest box holding every point and control point, as (min, max).
+    /// Curves stay inside it, `None` for an empty path.
+    ///
+    pub fn bounds(&self) -> Option<(Point, Point)> {
+        let mut bounds: Option<(Point, Point)> = None;
+        let mut add = |p: Point| {
+            let (min, max) = bounds.get_or_insert((p, p));
+
+            *min = Point {
+                x: min.x.min(p.x),
+                y: min.y.min(p.y),
+            };
+            *max = Point {
+                x: max.x.max(p.x),
+                y: max.y.max(p.y),
+            };
+        };
+
+        for command in self.commands.iter() {
+            match *command {
+                PathCommand::MoveTo(p) | PathCommand::LineTo(p) => add(p),
+                PathCommand::QuadTo(p1, p) => {
+                    add(p1);
+                    add(p);
+                }
+                PathCommand::CubicTo(p1, p2, p) => {
+                    add(p1);
+                    add(p2);
+                    add(p);
+                }
+                PathCommand::Close => {}
+            }
+        }
+
+        bounds
+    }
+
+    pub fn transform(&self, transform: &Transform) -> Path {
+        let commands = self
+            .commands
+            .iter()
+            .map(|command| match *command {
+                PathCommand::MoveTo(p) => PathCommand::MoveTo(transform.apply(p)),
+                PathCommand::LineTo(p) => PathCommand::LineTo(transform.apply(p)),
+                PathCommand::QuadTo(p1, p) => {
+                    PathCommand::QuadTo(transform.apply(p1), transform.apply(p))
+                }
+                PathCommand::CubicTo(p1, p2, p) => PathCommand::CubicTo(
+                    transform.apply(p1),
+                    transform.apply(p2),
+                    transform.apply(p),
+                ),
+                PathCommand::Close => PathCommand::Close,
+            })
+            .collect();
+
+        Path { commands }
+    }
+
+    ///
+    /// Exact area enclosed by the path, with every subpath implicitly closed as when filling.
+    /// Positive when the outline turns from +x towards +y (clockwise on screen), regions wound
+    /// several times count several times. Curves are integrated in closed form, not flattened.
+    ///
+    pub fn signed_area(&self) -> f32 {
+        let cross = |a: Point, b: Point| a.x as f64 * b.y as f64 - a.y as f64 * b.x as f64;
+        let mut start = Point { x: 0.0, y: 0.0 };
+        let mut last = start;
+        // Twice the area, each segment adds the integral of x dy - y dx along it
+        let mut sum = 0.0_f64;
+
+        for command in self.commands.iter() {
+            match *command {
+                PathCommand::MoveTo(p) => {
+                    sum += cross(last, start);
+                    start = p;
+                    last = p;
+                }
+                PathCommand::LineTo(p) => {
+                    sum += cross(last, p);
+                    last = p;
+                }
+                PathCommand::QuadTo(p1, p) => {
+                    sum += (2.0 * cross(last, p1) + cross(last, p) + 2.0 * cross(p1, p)) / 3.0;
+                    last = p;
+                }
+                PathCommand::CubicTo(p1, p2, p) => {
+                    sum += (6.0 * cross(last, p1)
+                        + 3.0 * cross(last, p2)
+                        + cross(last, p)
+                        + 3.0 * cross(p1, p2)
+                        + 3.0 * cross(p1, p)
+                        + 6.0 * cross(p2, p))
+                        / 10.0;
+                    last = p;
+                }
+                PathCommand::Close => {
+                    sum += cross(last, start);
+                    last = start;
+                }
+            }
+        }
+
+        sum += cross(last, start);
+        (0.5 * sum) as f32
+    }
+
+    ///
+    /// Converts the path to polylines, quadratic segments are flattened with `method`
+    /// and cubic segments are first approximated by quadratics within `tolerance`.
+    ///
+    pub fn flatten(&self, tolerance: f32, method: impl Flattener) -> Vec<Polyline> {
+        let mut collector = PolylineCollector {
+            result: Vec::new(),
+            current: Vec::new(),
+        };
+
+        self.flatten_into(tolerance, method, &mut collector);
+        collector.result
+    }
+
+    ///
+    /// Same as `flatten`, streaming the points into `sink` instead of allocating polylines
+    ///
+    pub fn flatten_into(&self, tolerance: f32, method: impl Flattener, sink: &mut impl PathSink) {
+        let mut start = Point { x: 0.0, y: 0.0 };
+        let mut last = start;
+        // Whether `move_to` was sent for the current subpath
+        let mut open = false;
+
+        fn begin(sink: &mut impl PathSink, open: &mut bool, last: Point) {
+            if !*open {
+                sink.move_to(last);
+                *open = true;
+            }
+        }
+
+        for command in self.commands.iter() {
+            match *command {
+                PathCommand::MoveTo(p) => {
+                    if open {
+                        sink.end(false);
+                        open = false;
+                    }
+                    start = p;
+                    last = p;
+                }
+                PathCommand::LineTo(p) => {
+                    begin(sink, &mut open, last);
+                    sink.line_to(p);
+                    last = p;
+                }
+                PathCommand::QuadTo(p1, p) => {
+                    begin(sink, &mut open, last);
+                    for p in QuadraticBezier::new(last, p1, p)
+                        .flatten(tolerance, &method)
+                        .skip(1)
+                    {
+                        sink.line_to(p);
+                    }
+                    last = p;
+                }
+                PathCommand::CubicTo(p1, p2, p) => {
+                    begin(sink, &mut open, last);
+                    for q in CubicQuadratics::new(last, p1, p2, p, tolerance) {
+                        for p in q.flatten(tolerance, &method).skip(1) {
+                            sink.line_to(p);
+                        }
+                    }
+                    last = p;
+                }
+                PathCommand::Close => {
+                    if open {
+                        sink.end(true);
+                        open = false;
+                    }
+                    last = start;
+                }
+            }
+        }
+
+        if open {
+            sink.end(false);
+        }
+    }
+
+    ///
+    /// Parses SVG path data (the `d` attribute), arcs are converted to quadratic segments
+    ///
+    pub fn from_svg(data: &str) -> Result<Path, String> {
+        let mut parser = PathDataParser {
+            bytes: data.as_bytes(),
+            pos: 0,
+        };
+        let mut path = Path::new();
+        let mut command = None::<u8>;
+        let mut current = Point { x: 0.0, y: 0.0 };
+        let mut start = current;
+        // Reflected control points for the smooth curve commands
+        let mut last_quad_ctrl = None::<Point>;
+        let mut last_cubic_ctrl = None::<Point>;
+
+        loop {
+            parser.skip_separators();
+
+            if parser.at_end() {
+                break;
+            }
+
+            if let Some(c) = parser.peek_command() {
+                parser.pos += 1;
+                command = Some(c);
+            } else if command.is_none() {
+                return Err(format!(
+                    "Path data must start with a command at {}",
+                    parser.pos
+                ));
+            }
+
+            let c = command.unwrap();
+            let relative = c.is_ascii_lowercase();
+            let offset = |p: Point, current: Point| {
+                if relative {
+                    Point {
+                        x: p.x + current.x,
+                        y: p.y + current.y,
+                    }
+                } else {
+                    p
+                }
+            };
+
+            match c.to_ascii_uppercase() {
+                b'M' => {
+                    let p = offset(parser.point()?, current);
+                    path.move_to(p);
+                    current = p;
+                    start = p;
+                    // Subsequent coordinate pairs are implicit line-tos
+                    command = Some(if relative { b'l' } else { b'L' });
+                    last_quad_ctrl = None;
+                    last_cubic_ctrl = None;
+                }
+                b'L' => {
+                    let p = offset(parser.point()?, current);
+                    path.line_to(p);
+                    current = p;
+                    last_quad_ctrl = None;
+                    last_cubic_ctrl = None;
+                }
+                b'H' => {
+                    let x = parser.number()?;
+                    let p = Point {
+                        x: if relative { current.x + x } else { x },
+                        y: current.y,
+                    };
+                    path.line_to(p);
+                    current = p;
+                    last_quad_ctrl = None;
+                    last_cubic_ctrl = None;
+                }
+                b'V' => {
+                    let y = parser.number()?;
+                    let p = Point {
+                        x: current.x,
+                        y: if relative { current.y + y } else { y },
+                    };
+                    path.line_to(p);
+                    current = p;
+                    last_quad_ctrl = None;
+                    last_cubic_ctrl = None;
+                }
+                b'Q' => {
+                    let p1 = offset(parser.point()?, current);
+                    let p = offset(parser.point()?, current);
+                    path.quad_to(p1, p);
+                    current = p;
+                    last_quad_ctrl = Some(p1);
+                    last_cubic_ctrl = None;
+                }
+                b'T' => {
+                    let p1 = reflect(last_quad_ctrl, current);
+                    let p = offset(parser.point()?, current);
+                    path.quad_to(p1, p);
+                    current = p;
+                    last_quad_ctrl = Some(p1);
+                    last_cubic_ctrl = None;
+                }
+                b'C' => {
+                    let p1 = offset(parser.point()?, current);
+                    let p2 = offset(parser.point()?, current);
+                    let p = offset(parser.point()?, current);
+                    path.cubic_to(p1, p2, p);
+                    current = p;
+                    last_quad_ctrl = None;
+                    last_cubic_ctrl = Some(p2);
+                }
+                b'S' => {
+                    let p1 = reflect(last_cubic_ctrl, current);
+                    let p2 = offset(parser.point()?, current);
+                    let p = offset(parser.point()?, current);
+                    path.cubic_to(p1, p2, p);
+                    current = p;
+                    last_quad_ctrl = None;
+                    last_cubic_ctrl = Some(p2);
+                }
+                b'A' => {
+                    let rx = parser.number()?;
+                    let ry = parser.number()?;
+                    let x_axis_rotation = parser.number()?;
+                    let large_arc = parser.flag()?;
+                    let sweep = parser.flag()?;
+                    let p = offset(parser.point()?, current);
+                    arc_to(
+                        &mut path,
+                        current,
+                        p,
+                        rx,
+                        ry,
+                        x_axis_rotation.to_radians(),
+                        large_arc,
+                        sweep,
+                    );
+                    current = p;
+                    last_quad_ctrl = None;
+                    last_cubic_ctrl = None;
+                }
+                b'Z' => {
+                    path.close();
+                    current = start;
+                    command = None;
+                    last_quad_ctrl = None;
+                    last_cubic_ctrl = None;
+                }
+                _ => {
+                    return Err(format!(
+                        "Unknown path command '{}' at {}",
+                        c as char,
+                        parser.pos - 1
+                    ))
+                }
+            }
+        }
+
+        Ok(path)
+    }
+}
+
+fn reflect(control: Option<Point>, current: Point) -> Point {
+    match control {
+        Some(p) => Point {
+            x: 2.0 * current.x - p.x,
+            y: 2.0 * current.y - p.y,
+        },
+        None => current,
+    }
+}
+
+///
+/// Splits a cubic into quadratics, the number of pieces comes from the error bound used by kurbo:
+/// https://github.com/linebender/kurbo/blob/main/kurbo/src/cubicbez.rs
+///
+pub fn cubic_to_quadratics(
+    p0: Point,
+    p1: Point,
+    p2: Point,
+    p3: Point,
+    tolerance: f32,
+) -> Vec<QuadraticBezier> {
+    CubicQuadratics::new(p0, p1, p2, p3, tolerance).collect()
+}
+
+///
+/// Iterator over the quadratics of `cubic_to_quadratics`, computed on the fly
+///
+#[derive(Debug, Clone)]
+pub struct CubicQuadratics {
+    points: [Point; 4],
+    n: usize,
+    i: usize,
+}
+
+impl CubicQuadratics {
+    pub fn new(p0: Point, p1: Point, p2: Point, p3: Point, tolerance: f32) -> Self {
+        let dx = 3.0 * (p2.x - p1.x) - (p3.x - p0.x);
+        let dy = 3.0 * (p2.y - p1.y) - (p3.y - p0.y);
+        let err = dx * dx + dy * dy;
+        let max_err = 432.0 * tolerance * tolerance;
+        // 0 / 0 with a zero tolerance on a cubic that is a quadratic gives NaN, and one piece
+        // covers it exactly
+        let n = bounded_segments(f32::powf(err / max_err, 1.0 / 6.0));
+
+        Self {
+            points: [p0, p1, p2, p3],
+            n,
+            i: 0,
+        }
+    }
+}
+
+impl Iterator for CubicQuadratics {
+    type Item = QuadraticBezier;
+
+    fn next(&mut self) -> Option<QuadraticBezier> {
+        if self.i >= self.n {
+            return None;
+        }
+
+        let [p0, p1, p2, p3] = self.points;
+        let eval = |t: f32| {
+            let mt = 1.0 - t;
+            let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+            Point {
+                x: a * p0.x + b * p1.x + c * p2.x + d * p3.x,
+                y: a * p0.y + b * p1.y + c * p2.y + d * p3.y,
+            }
+        };
+        let derivative = |t: f32| {
+            let mt = 1.0 - t;
+            let (a, b, c) = (3.0 * mt * mt, 6.0 * mt * t, 3.0 * t * t);
+            Point {
+                x: a * (p1.x - p0.x) + b * (p2.x - p1.x) + c * (p3.x - p2.x),
+                y: a * (p1.y - p0.y) + b * (p2.y - p1.y) + c * (p3.y - p2.y),
+            }
+        };
+
+        let t0 = self.i as f32 / self.n as f32;
+        let t1 = (self.i + 1) as f32 / self.n as f32;
+        let dt = t1 - t0;
+        let (q0, q2) = (eval(t0), eval(t1));
+        let (d0, d1) = (derivative(t0), derivative(t1));
+        // Control point of the cubic subsegment's midpoint quadratic: (3 * (c1 + c2) - q0 - q2) / 4
+        let q1 = Point {
+            x: 0.5 * (q0.x + q2.x) + 0.25 * dt * (d0.x - d1.x),
+            y: 0.5 * (q0.y + q2.y) + 0.25 * dt * (d0.y - d1.y),
+        };
+
+        self.i += 1;
+        Some(QuadraticBezier::new(q0, q1, q2))
+    }
+}
+
+///
+/// Appends an SVG elliptical arc from `from` to `to` as quadratic segments spanning at most 22.5° each.
+/// See: https://www.w3.org/TR/SVG11/implnote.html#ArcImplementationNotes
+///
+#[allow(clippy::too_many_arguments)]
+pub fn arc_to(
+    path: &mut Path,
+    from: Point,
+    to: Point,
+    rx: f32,
+    ry: f32,
+    x_axis_rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+) {
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+
+    if rx <= f32::EPSILON || ry <= f32::EPSILON {
+        path.line_to(to);
+        return;
+    }
+    if (from.x - to.x).abs() <= f32::EPSILON && (from.y - to.y).abs() <= f32::EPSILON {
+        return;
+    }
+
+    let (sin_phi, cos_phi) = x_axis_rotation.sin_cos();
+    let hx = 0.5 * (from.x - to.x);
+    let hy = 0.5 * (from.y - to.y);
+    let x1 = cos_phi * hx + sin_phi * hy;
+    let y1 = -sin_phi * hx + cos_phi * hy;
+
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let num = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let den = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let mut coef = f32::sqrt(f32::max(num / den, 0.0));
+    if large_arc == sweep {
+        coef = -coef;
+    }
+
+    let cx1 = coef * rx * y1 / ry;
+    let cy1 = -coef * ry * x1 / rx;
+    let cx = cos_phi * cx1 - sin_phi * cy1 + 0.5 * (from.x + to.x);
+    let cy = sin_phi * cx1 + cos_phi * cy1 + 0.5 * (from.y + to.y);
+
+    let angle = |ux: f32, uy: f32, vx: f32, vy: f32| {
+        let sign = if ux * vy - uy * vx < 0.0 { -1.0 } else { 1.0 };
+        let dot = (ux * vx + uy * vy) / (f32::hypot(ux, uy) * f32::hypot(vx, vy));
+        sign * dot.clamp(-1.0, 1.0).acos()
+    };
+
+    let theta1 = angle(1.0, 0.0, (x1 - cx1) / rx, (y1 - cy1) / ry);
+    let mut delta = angle(
+        (x1 - cx1) / rx,
+        (y1 - cy1) / ry,
+        (-x1 - cx1) / rx,
+        (-y1 - cy1) / ry,
+    );
+
+    if !sweep && delta > 0.0 {
+        delta -= 2.0 * core::f32::consts::PI;
+    } else if sweep && delta < 0.0 {
+        delta += 2.0 * core::f32::consts::PI;
+    }
+
+    let ellipse = Transform::new(
+        rx * cos_phi,
+        rx * sin_phi,
+        -ry * sin_phi,
+        ry * cos_phi,
+        cx,
+        cy,
+    );
+    let n = f32::ceil(delta.abs() / (core::f32::consts::PI / 8.0)).max(1.0) as usize;
+    let step = delta / n as f32;
+    let control_scale = (0.5 * step).cos().recip();
+
+    for i in 0..n {
+        let a0 = theta1 + step * i as f32;
+        let mid = a0 + 0.5 * step;
+        let a1 = a0 + step;
+        let control = ellipse.apply(Point {
+            x: mid.cos() * control_scale,
+            y: mid.sin() * control_scale,
+        });
+        let end = if i + 1 == n {
+            to
+        } else {
+            ellipse.apply(Point {
+                x: a1.cos(),
+                y: a1.sin(),
+            })
+        };
+
+        path.quad_to(control, end);
+    }
+}
+
+///
+/// Parses a list of numbers with the path data grammar, e.g. the `points` of a polygon
+///
+pub fn parse_numbers(data: &str) -> Result<Vec<f32>, String> {
+    let mut parser = PathDataParser {
+        bytes: data.as_bytes(),
+        pos: 0,
+    };
+    let mut result = Vec::new();
+
+    loop {
+        parser.skip_separators();
+
+        if parser.at_end() {
+            return Ok(result);
+        }
+
+        result.push(parser.number()?);
+    }
+}
+
+struct PathDataParser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PathDataParser<'a> {
+    fn at_end(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    fn skip_separators(&mut self) {
+        while !self.at_end() && matches!(self.bytes[self.pos], b' ' | b'\t' | b'\n' | b'\r' | b',')
+        {
+            self.pos += 1;
+        }
+    }
+
+    fn peek_command(&self) -> Option<u8> {
+        let c = self.bytes[self.pos];
+
+        // 'e' and 'E' are exponents, not commands
+        if c.is_ascii_alphabetic() && c != b'e' && c != b'E' {
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn number(&mut self) -> Result<f32, String> {
+        self.skip_separators();
+
+        let start = self.pos;
+        let bytes = self.bytes;
+        let mut seen_dot = false;
+
+        if !self.at_end() && matches!(bytes[self.pos], b'+' | b'-') {
+            self.pos += 1;
+        }
+
+        while !self.at_end() {
+            match bytes[self.pos] {
+                b'0'..=b'9' => self.pos += 1,
+                b'.' if !seen_dot => {
+                    seen_dot = true;
+                    self.pos += 1;
+                }
+                b'e' | b'E' => {
+                    self.pos += 1;
+                    if !self.at_end() && matches!(bytes[self.pos], b'+' | b'-') {
+                        self.pos += 1;
+                    }
+                    while !self.at_end() && bytes[self.pos].is_ascii_digit() {
+                        self.pos += 1;
+                    }
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        core::str::from_utf8(&bytes[start..self.pos])
+            .ok()
+            .and_then(|s| s.parse::<f32>().ok())
+            .ok_or_else(|| format!("Expected a number at {}", start))
+    }
+
+    fn point(&mut self) -> Result<Point, String> {
+        let x = self.number()?;
+        let y = self.number()?;
+
+        Ok(Point { x, y })
+    }
+
+    ///
+    /// Arc flags may be written without separators, e.g. "a1 1 0 01 5 5"
+    ///
+    fn flag(&mut self) -> Result<bool, String> {
+        self.skip_separators();
+
+        match self.bytes.get(self.pos) {
+            Some(b'0') => {
+                self.pos += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.pos += 1;
+                Ok(true)
+            }
+            _ => Err(format!("Expected an arc flag at {}", self.pos)),
+        }
+    }
+}
@@ -0,0 +1,232 @@
+//!
+//! Deliberately slow reference renderer. Every pixel is point sampled on an N×N grid and each
+//! sample gets the exact winding number of the unflattened path, cubics included. The result
+//! is there to measure the error of `Rasterizer` and of the flattening methods, not to be fast.
+//!
+
+use crate::path::{Path, PathCommand};
+use crate::{Point, Rasterizer};
+
+///
+/// Every segment of the path as a cubic in f64, lines and quadratics are degree elevated
+///
+struct Cubic {
+    x: [f64; 4],
+    y: [f64; 4],
+}
+
+impl Cubic {
+    fn new(p0: Point, p1: Point, p2: Point, p3: Point) -> Self {
+        Self {
+            x: [p0.x as f64, p1.x as f64, p2.x as f64, p3.x as f64],
+            y: [p0.y as f64, p1.y as f64, p2.y as f64, p3.y as f64],
+        }
+    }
+
+    fn line(p0: Point, p1: Point) -> Self {
+        Self::new(p0, p0.lerp(p1, 1.0 / 3.0), p0.lerp(p1, 2.0 / 3.0), p1)
+    }
+
+    fn quadratic(p0: Point, p1: Point, p2: Point) -> Self {
+        Self::new(p0, p0.lerp(p1, 2.0 / 3.0), p2.lerp(p1, 2.0 / 3.0), p2)
+    }
+
+    fn eval(c: &[f64; 4], t: f64) -> f64 {
+        let mt = 1.0 - t;
+
+        mt * mt * mt * c[0] + 3.0 * mt * mt * t * c[1] + 3.0 * mt * t * t * c[2] + t * t * t * c[3]
+    }
+
+    ///
+    /// Parameters splitting the curve into pieces monotonic in y, 0.0 and 1.0 included
+    ///
+    fn monotonic_in_y(&self) -> Vec<f64> {
+        let y = &self.y;
+        // dy/dt is 3 * (a * t^2 + b * t + c)
+        let a = y[3] - 3.0 * y[2] + 3.0 * y[1] - y[0];
+        let b = 2.0 * (y[2] - 2.0 * y[1] + y[0]);
+        let c = y[1] - y[0];
+        let mut ts = vec![0.0, 1.0];
+
+        if a.abs() <= 1e-12 * (b.abs() + c.abs()) {
+            ts.push(-c / b);
+        } else {
+            let discriminant = b * b - 4.0 * a * c;
+
+            if discriminant >= 0.0 {
+                let root = discriminant.sqrt();
+                ts.push((-b - root) / (2.0 * a));
+                ts.push((-b + root) / (2.0 * a));
+            }
+        }
+
+        ts.retain(|t| (0.0..=1.0).contains(t));
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ts
+    }
+}
+
+///
+/// Piece of a segment on which y is monotonic, between `t0` and `t1`
+///
+struct Monotonic {
+    segment: usize,
+    t0: f64,
+    t1: f64,
+    y0: f64,
+    y1: f64,
+}
+
+fn path_segments(path: &Path) -> Vec<Cubic> {
+    let mut segments = Vec::new();
+    let mut start = Point { x: 0.0, y: 0.0 };
+    let mut last = start;
+
+    for command in path.commands.iter() {
+        match *command {
+            PathCommand::MoveTo(p) => {
+                segments.push(Cubic::line(last, start));
+                start = p;
+                last = p;
+            }
+            PathCommand::LineTo(p) => {
+                segments.push(Cubic::line(last, p));
+                last = p;
+            }
+            PathCommand::QuadTo(p1, p) => {
+                segments.push(Cubic::quadratic(last, p1, p));
+                last = p;
+            }
+            PathCommand::CubicTo(p1, p2, p) => {
+                segments.push(Cubic::new(last, p1, p2, p));
+                last = p;
+            }
+            PathCommand::Close => {
+                segments.push(Cubic::line(last, start));
+                last = start;
+            }
+        }
+    }
+
+    segments.push(Cubic::line(last, start));
+    segments
+}
+
+///
+/// Winding of every pixel, averaged over `samples` × `samples` points, in the layout of
+/// `Rasterizer::accumulation_buffer` once prefix summed: `width` × `height` values row by
+/// row, with the same sign, so that `FillRule::coverage` applies as is. Subpaths are closed
+/// implicitly and parts left of the canvas still wind the pixels right of them, as in
+/// `Rasterizer::draw_path`.
+///
+pub fn supersample(path: &Path, width: usize, height: usize, samples: usize) -> Vec<f32> {
+    let segments = path_segments(path);
+    let mut pieces = Vec::<Monotonic>::new();
+
+    for (index, segment) in segments.iter().enumerate() {
+        for span in segment.monotonic_in_y().windows(2) {
+            let (t0, t1) = (span[0], span[1]);
+            let (y0, y1) = (Cubic::eval(&segment.y, t0), Cubic::eval(&segment.y, t1));
+
+            // Horizontal pieces never cross a sample row
+            if y0 != y1 {
+                pieces.push(Monotonic {
+                    segment: index,
+                    t0,
+                    t1,
+                    y0,
+                    y1,
+                });
+            }
+        }
+    }
+
+    let mut result = vec![0.0_f32; width * height];
+    let mut crossings = Vec::<(f64, f64)>::new();
+    let weight = 1.0 / (samples * samples) as f64;
+
+    for y in 0..height {
+        let row = &mut result[y * width..(y + 1) * width];
+        let mut winding_sum = vec![0.0_f64; width];
+
+        for sy in 0..samples {
+            let sample_y = y as f64 + (sy as f64 + 0.5) / samples as f64;
+
+            crossings.clear();
+
+            for piece in pieces.iter() {
+                let (min, max) = (piece.y0.min(piece.y1), piece.y0.max(piece.y1));
+
+                // Half open so that a sample on a shared end point counts once
+                if sample_y < min || sample_y >= max {
+                    continue;
+                }
+
+                let segment = &segments[piece.segment];
+                let (mut t0, mut t1) = (piece.t0, piece.t1);
+
+                // Bisection, y is monotonic on the piece
+                for _ in 0..64 {
+                    let t = 0.5 * (t0 + t1);
+
+                    if (Cubic::eval(&segment.y, t) < sample_y) == (piece.y0 < piece.y1) {
+                        t0 = t;
+                    } else {
+                        t1 = t;
+                    }
+                }
+
+                let direction = if piece.y1 > piece.y0 { 1.0 } else { -1.0 };
+                crossings.push((Cubic::eval(&segment.x, 0.5 * (t0 + t1)), direction));
+            }
+
+            crossings.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+
+            // Sweep the samples of the row left to right, adding crossings as they are passed
+            let mut next = 0;
+            let mut winding = 0.0;
+
+            for (x, sum) in winding_sum.iter_mut().enumerate() {
+                for sx in 0..samples {
+                    let sample_x = x as f64 + (sx as f64 + 0.5) / samples as f64;
+
+                    while next < crossings.len() && crossings[next].0 < sample_x {
+                        winding += crossings[next].1;
+                        next += 1;
+                    }
+
+                    *sum += winding;
+                }
+            }
+        }
+
+        for (value, sum) in row.iter_mut().zip(winding_sum) {
+            *value = (sum * weight) as f32;
+        }
+    }
+
+    result
+}
+
+///
+/// Rasterizer whose accumulation buffer resolves to `coverage`, a buffer laid out as the
+/// result of `supersample`, so that it renders and compares like any other
+///
+pub fn to_rasterizer(coverage: &[f32], width: usize, height: usize) -> Rasterizer {
+    let mut accumulation_buffer = coverage.to_vec();
+
+    if width > 0 {
+        for row in accumulation_buffer.chunks_exact_mut(width) {
+            for x in (1..width).rev() {
+                row[x] -= row[x - 1];
+            }
+        }
+    }
+
+    Rasterizer {
+        width,
+        height,
+        accumulation_buffer,
+        tolerance: 0.0,
+    }
+}
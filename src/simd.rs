@@ -0,0 +1,643 @@
+//!
+//! Vectorized kernels for the hot loops of the rasterizer. On x86_64 each function runs an
+//! AVX2 implementation when the CPU has it, detected at runtime with std and at compile time
+//! without, and the SSE2 one otherwise, which every x86_64 target enables. Other targets run
+//! the scalar loops, which also handle the tails of the vector loops. The vector versions
+//! sum in a different order, so results may differ from the scalar ones by a few ulps, which
+//! stays well below 1/255 once converted to 8 bits.
+//!
+
+use crate::Color;
+
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::*;
+
+#[cfg(all(target_arch = "x86_64", feature = "std"))]
+fn has_avx2() -> bool {
+    is_x86_feature_detected!("avx2")
+}
+
+///
+/// Without std there is no runtime detection, only what the target enables at compile time
+///
+#[cfg(all(target_arch = "x86_64", not(feature = "std")))]
+fn has_avx2() -> bool {
+    cfg!(target_feature = "avx2")
+}
+
+///
+/// Adds `value` to every cell, used for the cells fully covered by a line on a row
+///
+pub fn add_constant(cells: &mut [f32], value: f32) {
+    #[cfg(target_arch = "x86_64")]
+    if has_avx2() {
+        // Safety: AVX2 support was checked above
+        unsafe { add_constant_avx2(cells, value) };
+        return;
+    }
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    // Safety: the target enables SSE2
+    unsafe {
+        add_constant_sse2(cells, value)
+    };
+
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "sse2")))]
+    add_constant_scalar(cells, value);
+}
+
+///
+/// Running sum of `cells` into `out`, as absolute values. `out` must be as long as `cells`.
+///
+pub fn prefix_sum_abs(cells: &[f32], out: &mut [f32]) {
+    assert_eq!(cells.len(), out.len());
+
+    #[cfg(target_arch = "x86_64")]
+    if has_avx2() {
+        // Safety: AVX2 support was checked above
+        unsafe { prefix_sum_abs_avx2(cells, out) };
+        return;
+    }
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    // Safety: the target enables SSE2
+    unsafe {
+        prefix_sum_abs_sse2(cells, out)
+    };
+
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "sse2")))]
+    prefix_sum_abs_scalar(0.0, cells, out);
+}
+
+///
+/// Converts coverage values to 8 bits, values outside of [0, 1] are clamped
+///
+pub fn coverage_to_u8(coverage: &[f32], out: &mut [u8]) {
+    assert_eq!(coverage.len(), out.len());
+
+    #[cfg(target_arch = "x86_64")]
+    if has_avx2() {
+        // Safety: AVX2 support was checked above
+        unsafe { coverage_to_u8_avx2(coverage, out) };
+        return;
+    }
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    // Safety: the target enables SSE2
+    unsafe {
+        coverage_to_u8_sse2(coverage, out)
+    };
+
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "sse2")))]
+    coverage_to_u8_scalar(coverage, out);
+}
+
+///
+/// Blends `fg` over `bg` by each coverage value and writes RGBA8 pixels into `out`,
+/// which holds 4 bytes per coverage value. The alpha channel is the one of `bg`.
+///
+pub fn blend_to_rgba8(coverage: &[f32], fg: Color, bg: Color, out: &mut [u8]) {
+    assert_eq!(coverage.len() * 4, out.len());
+
+    #[cfg(target_arch = "x86_64")]
+    if has_avx2() {
+        // Safety: AVX2 support was checked above
+        unsafe { blend_to_rgba8_avx2(coverage, fg, bg, out) };
+        return;
+    }
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    // Safety: the target enables SSE2
+    unsafe {
+        blend_to_rgba8_sse2(coverage, fg, bg, out)
+    };
+
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "sse2")))]
+    blend_to_rgba8_scalar(coverage, fg, bg, out);
+}
+
+///
+/// Mixes all four channels of `bg` and `fg` by each coverage value, as bg + (fg - bg) * a,
+/// into the RGBA pixels of `out`, which holds 4 values per coverage value. Every version
+/// computes each channel with the same two operations, so they agree bit for bit.
+///
+pub fn mix_to_rgba(coverage: &[f32], fg: Color, bg: Color, out: &mut [f32]) {
+    assert_eq!(coverage.len() * 4, out.len());
+
+    #[cfg(target_arch = "x86_64")]
+    if has_avx2() {
+        // Safety: AVX2 support was checked above
+        unsafe { mix_to_rgba_avx2(coverage, fg, bg, out) };
+        return;
+    }
+
+    #[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+    // Safety: the target enables SSE2
+    unsafe {
+        mix_to_rgba_sse2(coverage, fg, bg, out)
+    };
+
+    #[cfg(not(all(target_arch = "x86_64", target_feature = "sse2")))]
+    mix_to_rgba_scalar(coverage, fg, bg, out);
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0) as u8
+}
+
+pub(crate) fn add_constant_scalar(cells: &mut [f32], value: f32) {
+    for cell in cells.iter_mut() {
+        *cell += value;
+    }
+}
+
+///
+/// Starts the running sum at `acc`, the sum of the cells before these ones
+///
+pub(crate) fn prefix_sum_abs_scalar(mut acc: f32, cells: &[f32], out: &mut [f32]) {
+    for (cell, value) in cells.iter().zip(out.iter_mut()) {
+        acc += cell;
+        *value = acc.abs();
+    }
+}
+
+pub(crate) fn coverage_to_u8_scalar(coverage: &[f32], out: &mut [u8]) {
+    for (value, byte) in coverage.iter().zip(out.iter_mut()) {
+        *byte = to_u8(*value);
+    }
+}
+
+pub(crate) fn blend_to_rgba8_scalar(coverage: &[f32], fg: Color, bg: Color, out: &mut [u8]) {
+    for (a, pixel) in coverage.iter().zip(out.chunks_exact_mut(4)) {
+        pixel[0] = to_u8(fg.r * a + bg.r * (1.0 - a));
+        pixel[1] = to_u8(fg.g * a + bg.g * (1.0 - a));
+        pixel[2] = to_u8(fg.b * a + bg.b * (1.0 - a));
+        pixel[3] = to_u8(bg.a);
+    }
+}
+
+pub(crate) fn mix_to_rgba_scalar(coverage: &[f32], fg: Color, bg: Color, out: &mut [f32]) {
+    let (bg, fg) = ([bg.r, bg.g, bg.b, bg.a], [fg.r, fg.g, fg.b, fg.a]);
+
+    for (a, pixel) in coverage.iter().zip(out.chunks_exact_mut(4)) {
+        for c in 0..4 {
+            pixel[c] = bg[c] + (fg[c] - bg[c]) * a;
+        }
+    }
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[target_feature(enable = "sse2")]
+unsafe fn add_constant_sse2(cells: &mut [f32], value: f32) {
+    let mut chunks = cells.chunks_exact_mut(4);
+    let value_x4 = _mm_set1_ps(value);
+
+    for chunk in &mut chunks {
+        let pointer = chunk.as_mut_ptr();
+        _mm_storeu_ps(pointer, _mm_add_ps(_mm_loadu_ps(pointer), value_x4));
+    }
+
+    add_constant_scalar(chunks.into_remainder(), value);
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[target_feature(enable = "sse2")]
+unsafe fn prefix_sum_abs_sse2(cells: &[f32], out: &mut [f32]) {
+    let sign_mask = _mm_set1_ps(-0.0);
+    let mut carry = _mm_setzero_ps();
+    let mut chunks = cells.chunks_exact(4);
+    let mut out_chunks = out.chunks_exact_mut(4);
+
+    for (chunk, out_chunk) in (&mut chunks).zip(&mut out_chunks) {
+        // In-register inclusive scan: [a, a+b, a+b+c, a+b+c+d]
+        let mut x = _mm_loadu_ps(chunk.as_ptr());
+        x = _mm_add_ps(x, _mm_castsi128_ps(_mm_slli_si128(_mm_castps_si128(x), 4)));
+        x = _mm_add_ps(x, _mm_castsi128_ps(_mm_slli_si128(_mm_castps_si128(x), 8)));
+        x = _mm_add_ps(x, carry);
+        carry = _mm_shuffle_ps(x, x, 0xff);
+
+        _mm_storeu_ps(out_chunk.as_mut_ptr(), _mm_andnot_ps(sign_mask, x));
+    }
+
+    prefix_sum_abs_scalar(
+        _mm_cvtss_f32(carry),
+        chunks.remainder(),
+        out_chunks.into_remainder(),
+    );
+}
+
+///
+/// Clamps 4 values to [0, 1] and scales them to [0, 255], truncating like `as u8`
+///
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[target_feature(enable = "sse2")]
+unsafe fn to_i32_x4(x: __m128) -> __m128i {
+    let clamped = _mm_min_ps(_mm_max_ps(x, _mm_setzero_ps()), _mm_set1_ps(1.0));
+
+    _mm_cvttps_epi32(_mm_mul_ps(clamped, _mm_set1_ps(255.0)))
+}
+
+///
+/// bg + (fg - bg) * a for 4 pixels, converted like `to_i32_x4`
+///
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[target_feature(enable = "sse2")]
+unsafe fn channel_x4(a: __m128, fg: f32, bg: f32) -> __m128i {
+    to_i32_x4(_mm_add_ps(
+        _mm_set1_ps(bg),
+        _mm_mul_ps(_mm_set1_ps(fg - bg), a),
+    ))
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[target_feature(enable = "sse2")]
+unsafe fn coverage_to_u8_sse2(coverage: &[f32], out: &mut [u8]) {
+    let mut chunks = coverage.chunks_exact(16);
+    let mut out_chunks = out.chunks_exact_mut(16);
+
+    for (chunk, out_chunk) in (&mut chunks).zip(&mut out_chunks) {
+        let p = chunk.as_ptr();
+        let a = to_i32_x4(_mm_loadu_ps(p));
+        let b = to_i32_x4(_mm_loadu_ps(p.add(4)));
+        let c = to_i32_x4(_mm_loadu_ps(p.add(8)));
+        let d = to_i32_x4(_mm_loadu_ps(p.add(12)));
+        let bytes = _mm_packus_epi16(_mm_packs_epi32(a, b), _mm_packs_epi32(c, d));
+
+        _mm_storeu_si128(out_chunk.as_mut_ptr() as *mut __m128i, bytes);
+    }
+
+    coverage_to_u8_scalar(chunks.remainder(), out_chunks.into_remainder());
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[target_feature(enable = "sse2")]
+unsafe fn blend_to_rgba8_sse2(coverage: &[f32], fg: Color, bg: Color, out: &mut [u8]) {
+    let mut chunks = coverage.chunks_exact(4);
+    let mut out_chunks = out.chunks_exact_mut(16);
+    let alpha = _mm_slli_epi32(_mm_set1_epi32(to_u8(bg.a) as i32), 24);
+
+    for (chunk, out_chunk) in (&mut chunks).zip(&mut out_chunks) {
+        let a = _mm_loadu_ps(chunk.as_ptr());
+        let r = channel_x4(a, fg.r, bg.r);
+        let g = _mm_slli_epi32(channel_x4(a, fg.g, bg.g), 8);
+        let b = _mm_slli_epi32(channel_x4(a, fg.b, bg.b), 16);
+        // Little endian, so each 32 bit lane is stored as R, G, B, A
+        let pixels = _mm_or_si128(_mm_or_si128(r, g), _mm_or_si128(b, alpha));
+
+        _mm_storeu_si128(out_chunk.as_mut_ptr() as *mut __m128i, pixels);
+    }
+
+    blend_to_rgba8_scalar(chunks.remainder(), fg, bg, out_chunks.into_remainder());
+}
+
+#[cfg(all(target_arch = "x86_64", target_feature = "sse2"))]
+#[target_feature(enable = "sse2")]
+unsafe fn mix_to_rgba_sse2(coverage: &[f32], fg: Color, bg: Color, out: &mut [f32]) {
+    // One pixel per register, the channels are the lanes
+    let bg_x4 = _mm_setr_ps(bg.r, bg.g, bg.b, bg.a);
+    let delta = _mm_sub_ps(_mm_setr_ps(fg.r, fg.g, fg.b, fg.a), bg_x4);
+
+    for (a, pixel) in coverage.iter().zip(out.chunks_exact_mut(4)) {
+        let mixed = _mm_add_ps(bg_x4, _mm_mul_ps(delta, _mm_set1_ps(*a)));
+
+        _mm_storeu_ps(pixel.as_mut_ptr(), mixed);
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn add_constant_avx2(cells: &mut [f32], value: f32) {
+    let mut chunks = cells.chunks_exact_mut(8);
+    let value_x8 = _mm256_set1_ps(value);
+
+    for chunk in &mut chunks {
+        let pointer = chunk.as_mut_ptr();
+        _mm256_storeu_ps(pointer, _mm256_add_ps(_mm256_loadu_ps(pointer), value_x8));
+    }
+
+    add_constant_scalar(chunks.into_remainder(), value);
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn prefix_sum_abs_avx2(cells: &[f32], out: &mut [f32]) {
+    let sign_mask = _mm256_set1_ps(-0.0);
+    let mut carry = _mm256_setzero_ps();
+    let mut chunks = cells.chunks_exact(8);
+    let mut out_chunks = out.chunks_exact_mut(8);
+
+    for (chunk, out_chunk) in (&mut chunks).zip(&mut out_chunks) {
+        // Scan of each 128 bit half as in `prefix_sum_abs_sse2`, then the total of the low
+        // half is added to the high one
+        let mut x = _mm256_loadu_ps(chunk.as_ptr());
+        x = _mm256_add_ps(
+            x,
+            _mm256_castsi256_ps(_mm256_slli_si256(_mm256_castps_si256(x), 4)),
+        );
+        x = _mm256_add_ps(
+            x,
+            _mm256_castsi256_ps(_mm256_slli_si256(_mm256_castps_si256(x), 8)),
+        );
+        let low_total = _mm256_shuffle_ps(x, x, 0xff);
+        x = _mm256_add_ps(x, _mm256_permute2f128_ps(low_total, low_total, 0x08));
+        x = _mm256_add_ps(x, carry);
+        let high = _mm256_permute2f128_ps(x, x, 0x11);
+        carry = _mm256_shuffle_ps(high, high, 0xff);
+
+        _mm256_storeu_ps(out_chunk.as_mut_ptr(), _mm256_andnot_ps(sign_mask, x));
+    }
+
+    prefix_sum_abs_scalar(
+        _mm256_cvtss_f32(carry),
+        chunks.remainder(),
+        out_chunks.into_remainder(),
+    );
+}
+
+///
+/// Clamps 8 values to [0, 1] and scales them to [0, 255], truncating like `as u8`
+///
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn to_i32_x8(x: __m256) -> __m256i {
+    let clamped = _mm256_min_ps(_mm256_max_ps(x, _mm256_setzero_ps()), _mm256_set1_ps(1.0));
+
+    _mm256_cvttps_epi32(_mm256_mul_ps(clamped, _mm256_set1_ps(255.0)))
+}
+
+///
+/// bg + (fg - bg) * a for 8 pixels, converted like `to_i32_x8`
+///
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn channel_x8(a: __m256, fg: f32, bg: f32) -> __m256i {
+    to_i32_x8(_mm256_add_ps(
+        _mm256_set1_ps(bg),
+        _mm256_mul_ps(_mm256_set1_ps(fg - bg), a),
+    ))
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn coverage_to_u8_avx2(coverage: &[f32], out: &mut [u8]) {
+    let mut chunks = coverage.chunks_exact(32);
+    let mut out_chunks = out.chunks_exact_mut(32);
+    // The packs work within each 128 bit half, this puts the groups of 4 bytes back in order
+    let order = _mm256_setr_epi32(0, 4, 1, 5, 2, 6, 3, 7);
+
+    for (chunk, out_chunk) in (&mut chunks).zip(&mut out_chunks) {
+        let p = chunk.as_ptr();
+        let a = to_i32_x8(_mm256_loadu_ps(p));
+        let b = to_i32_x8(_mm256_loadu_ps(p.add(8)));
+        let c = to_i32_x8(_mm256_loadu_ps(p.add(16)));
+        let d = to_i32_x8(_mm256_loadu_ps(p.add(24)));
+        let bytes = _mm256_packus_epi16(_mm256_packs_epi32(a, b), _mm256_packs_epi32(c, d));
+
+        _mm256_storeu_si256(
+            out_chunk.as_mut_ptr() as *mut __m256i,
+            _mm256_permutevar8x32_epi32(bytes, order),
+        );
+    }
+
+    coverage_to_u8_scalar(chunks.remainder(), out_chunks.into_remainder());
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn blend_to_rgba8_avx2(coverage: &[f32], fg: Color, bg: Color, out: &mut [u8]) {
+    let mut chunks = coverage.chunks_exact(8);
+    let mut out_chunks = out.chunks_exact_mut(32);
+    let alpha = _mm256_slli_epi32(_mm256_set1_epi32(to_u8(bg.a) as i32), 24);
+
+    for (chunk, out_chunk) in (&mut chunks).zip(&mut out_chunks) {
+        let a = _mm256_loadu_ps(chunk.as_ptr());
+        let r = channel_x8(a, fg.r, bg.r);
+        let g = _mm256_slli_epi32(channel_x8(a, fg.g, bg.g), 8);
+        let b = _mm256_slli_epi32(channel_x8(a, fg.b, bg.b), 16);
+        let pixels = _mm256_or_si256(_mm256_or_si256(r, g), _mm256_or_si256(b, alpha));
+
+        _mm256_storeu_si256(out_chunk.as_mut_ptr() as *mut __m256i, pixels);
+    }
+
+    blend_to_rgba8_scalar(chunks.remainder(), fg, bg, out_chunks.into_remainder());
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn mix_to_rgba_avx2(coverage: &[f32], fg: Color, bg: Color, out: &mut [f32]) {
+    // Two pixels per register, each in a 128 bit half
+    let bg_x4 = _mm_setr_ps(bg.r, bg.g, bg.b, bg.a);
+    let fg_x4 = _mm_setr_ps(fg.r, fg.g, fg.b, fg.a);
+    let bg_x8 = _mm256_set_m128(bg_x4, bg_x4);
+    let delta = _mm256_sub_ps(_mm256_set_m128(fg_x4, fg_x4), bg_x8);
+    let mut chunks = coverage.chunks_exact(2);
+    let mut out_chunks = out.chunks_exact_mut(8);
+
+    for (chunk, out_chunk) in (&mut chunks).zip(&mut out_chunks) {
+        let a = _mm256_set_m128(_mm_set1_ps(chunk[1]), _mm_set1_ps(chunk[0]));
+        let mixed = _mm256_add_ps(bg_x8, _mm256_mul_ps(delta, a));
+
+        _mm256_storeu_ps(out_chunk.as_mut_ptr(), mixed);
+    }
+
+    mix_to_rgba_scalar(chunks.remainder(), fg, bg, out_chunks.into_remainder());
+}
+
+///
+/// On x86_64 the dispatchers never reach the scalar kernels, and only one of the vector ones:
+/// compare each of them with the scalar kernels directly, the AVX2 ones when the CPU has it.
+/// The lengths leave remainders for the scalar tails of the vector loops.
+///
+#[cfg(all(test, target_arch = "x86_64"))]
+mod tests {
+    extern crate std;
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    const LENGTHS: [usize; 14] = [0, 1, 2, 3, 4, 5, 7, 8, 9, 15, 16, 17, 33, 71];
+
+    ///
+    /// Values around [0, 1] with some outside of it and a few special ones
+    ///
+    fn values(length: usize) -> Vec<f32> {
+        let mut state = 3_u64;
+
+        (0..length)
+            .map(|i| {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let unit = (state >> 40) as f32 / (1_u64 << 24) as f32;
+
+                match i % 13 {
+                    5 => 0.0,
+                    9 => 1.0,
+                    11 => -unit,
+                    12 => 1.0 + unit,
+                    _ => unit,
+                }
+            })
+            .collect()
+    }
+
+    fn has_avx2() -> bool {
+        std::is_x86_feature_detected!("avx2")
+    }
+
+    #[test]
+    fn add_constant_matches_scalar() {
+        for length in LENGTHS {
+            let mut scalar = values(length);
+            let (mut sse2, mut avx2) = (scalar.clone(), scalar.clone());
+
+            add_constant_scalar(scalar.as_mut_slice(), 0.375);
+            unsafe { add_constant_sse2(sse2.as_mut_slice(), 0.375) };
+            assert_eq!(sse2, scalar, "SSE2, length {}", length);
+
+            if has_avx2() {
+                unsafe { add_constant_avx2(avx2.as_mut_slice(), 0.375) };
+                assert_eq!(avx2, scalar, "AVX2, length {}", length);
+            }
+        }
+    }
+
+    #[test]
+    fn prefix_sum_abs_matches_scalar() {
+        for length in LENGTHS {
+            let cells = values(length)
+                .iter()
+                .map(|value| value - 0.5)
+                .collect::<Vec<f32>>();
+            let mut scalar = vec![0.0; length];
+
+            prefix_sum_abs_scalar(0.0, cells.as_slice(), scalar.as_mut_slice());
+
+            for (name, kernel, available) in [
+                (
+                    "SSE2",
+                    prefix_sum_abs_sse2 as unsafe fn(&[f32], &mut [f32]),
+                    true,
+                ),
+                ("AVX2", prefix_sum_abs_avx2, has_avx2()),
+            ] {
+                if !available {
+                    continue;
+                }
+
+                let mut simd = vec![0.0; length];
+
+                unsafe { kernel(cells.as_slice(), simd.as_mut_slice()) };
+
+                // Summed in another order
+                for (i, (a, b)) in simd.iter().zip(&scalar).enumerate() {
+                    assert!(
+                        (a - b).abs() <= 1e-5,
+                        "{}, length {} at {}",
+                        name,
+                        length,
+                        i
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn coverage_to_u8_matches_scalar() {
+        for length in LENGTHS {
+            let mut coverage = values(length);
+            if length > 2 {
+                coverage[2] = f32::NAN;
+            }
+            let mut scalar = vec![0_u8; length];
+
+            coverage_to_u8_scalar(coverage.as_slice(), scalar.as_mut_slice());
+
+            for (name, kernel, available) in [
+                (
+                    "SSE2",
+                    coverage_to_u8_sse2 as unsafe fn(&[f32], &mut [u8]),
+                    true,
+                ),
+                ("AVX2", coverage_to_u8_avx2, has_avx2()),
+            ] {
+                if available {
+                    let mut simd = vec![0_u8; length];
+
+                    unsafe { kernel(coverage.as_slice(), simd.as_mut_slice()) };
+                    assert_eq!(simd, scalar, "{}, length {}", name, length);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn blend_to_rgba8_matches_scalar() {
+        let (fg, bg) = (Color::yellow_green(), Color::steel_blue());
+
+        for length in LENGTHS {
+            let coverage = values(length);
+            let mut scalar = vec![0_u8; length * 4];
+
+            blend_to_rgba8_scalar(coverage.as_slice(), fg, bg, scalar.as_mut_slice());
+
+            for (name, kernel, available) in [
+                (
+                    "SSE2",
+                    blend_to_rgba8_sse2 as unsafe fn(&[f32], Color, Color, &mut [u8]),
+                    true,
+                ),
+                ("AVX2", blend_to_rgba8_avx2, has_avx2()),
+            ] {
+                if !available {
+                    continue;
+                }
+
+                let mut simd = vec![0_u8; length * 4];
+
+                unsafe { kernel(coverage.as_slice(), fg, bg, simd.as_mut_slice()) };
+
+                // Interpolated in another form, which may round to the other side of a step
+                for (i, (a, b)) in simd.iter().zip(&scalar).enumerate() {
+                    assert!(a.abs_diff(*b) <= 1, "{}, length {} at {}", name, length, i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn mix_to_rgba_matches_scalar() {
+        let fg = Color {
+            a: 0.75,
+            ..Color::yellow_green()
+        };
+        let bg = Color::steel_blue();
+
+        for length in LENGTHS {
+            let coverage = values(length);
+            let mut scalar = vec![0.0_f32; length * 4];
+
+            mix_to_rgba_scalar(coverage.as_slice(), fg, bg, scalar.as_mut_slice());
+
+            for (name, kernel, available) in [
+                (
+                    "SSE2",
+                    mix_to_rgba_sse2 as unsafe fn(&[f32], Color, Color, &mut [f32]),
+                    true,
+                ),
+                ("AVX2", mix_to_rgba_avx2, has_avx2()),
+            ] {
+                if available {
+                    let mut simd = vec![0.0_f32; length * 4];
+
+                    unsafe { kernel(coverage.as_slice(), fg, bg, simd.as_mut_slice()) };
+                    // Same operations in every lane
+                    assert_eq!(simd, scalar, "{}, length {}", name, length);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use crate::flatten::{ClosingSink, Flattener, LineSink};
+use crate::path::Path;
+use crate::rasterizer::{accumulate_line_with, clip_line, CellSink, FillRule};
+use crate::{Color, Point, QuadraticBezier};
+
+pub const TILE_SIZE: usize = 16;
+
+///
+/// Coverage of a tile crossed by at least one edge, `coverage` holds `TILE_SIZE` rows
+/// of `TILE_SIZE` values, columns and rows past the canvas are left at 0.0
+///
+#[derive(Debug, Clone)]
+pub struct Strip {
+    pub x: usize,
+    pub y: usize,
+    pub coverage: Vec<f32>,
+}
+
+///
+/// Run of pixels on row `y`, from `x0` up to but excluding `x1`, with a constant coverage
+///
+#[derive(Debug, Clone, Copy)]
+pub struct Span {
+    pub y: usize,
+    pub x0: usize,
+    pub x1: usize,
+    pub coverage: f32,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SparseCoverage {
+    pub strips: Vec<Strip>,
+    pub spans: Vec<Span>,
+}
+
+///
+/// Alternative to `Rasterizer` that only stores accumulation cells for the tiles that edges
+/// cross. The area between tiles on a row is constant and resolved into spans, so the memory
+/// and resolve time depend on the length of the outline instead of the size of the canvas.
+///
+pub struct SparseRasterizer {
+    pub width: usize,
+    pub height: usize,
+    pub tolerance: f32,
+    ///
+    /// Maps tile coordinates to the offset of their cells in `cells`
+    ///
+    tiles: HashMap<(usize, usize), usize>,
+    cells: Vec<f32>,
+}
+
+impl SparseRasterizer {
+    pub fn new(width: usize, height: usize, tolerance: f32) -> Self {
+        Self {
+            width,
+            height,
+            tolerance,
+            tiles: HashMap::new(),
+            cells: Vec::new(),
+        }
+    }
+
+    pub fn tile_count(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.tiles.clear();
+        self.cells.clear();
+    }
+
+    pub fn draw_line(&mut self, p0: Point, p1: Point) {
+        let (width, height) = (self.width, self.height);
+        let mut sink = TileCells {
+            tiles: &mut self.tiles,
+            cells: &mut self.cells,
+            last: (usize::MAX, usize::MAX, 0),
+        };
+
+        clip_line(width, height, p0, p1, |a, b| {
+            accumulate_line_with(width, 0..height, a, b, &mut sink);
+        });
+    }
+
+    pub fn draw_quadratic(&mut self, q: QuadraticBezier, method: impl Flattener) {
+        self.polyline(q.flatten(self.tolerance, method));
+    }
+
+    ///
+    /// Every subpath is implicitly closed, as needed for filling
+    ///
+    pub fn draw_path(&mut self, path: &Path, method: impl Flattener) {
+        path.flatten_into(self.tolerance, method, &mut ClosingSink::new(self));
+    }
+
+    ///
+    /// Resolves the tiles row by row, carrying the accumulated winding across the gaps
+    /// between tiles. Gives the same coverage as prefix summing a dense buffer.
+    ///
+    pub fn coverage(&self, fill_rule: FillRule) -> SparseCoverage {
+        let mut result = SparseCoverage::default();
+        let mut tiles = self
+            .tiles
+            .iter()
+            .map(|(&(tx, ty), &offset)| (ty, tx, offset))
+            .collect::<Vec<(usize, usize, usize)>>();
+
+        tiles.sort_unstable();
+
+        for row_tiles in tiles.chunk_by(|a, b| a.0 == b.0) {
+            let ty = row_tiles[0].0;
+            let mut carries = [0.0_f32; TILE_SIZE];
+            let mut cursor = 0_usize;
+            let rows = usize::min(TILE_SIZE, self.height - ty * TILE_SIZE);
+
+            for &(_, tx, offset) in row_tiles {
+                let x = tx * TILE_SIZE;
+                let columns = usize::min(TILE_SIZE, self.width - x);
+                let mut coverage = vec![0.0_f32; TILE_SIZE * TILE_SIZE];
+
+                for (row, carry) in carries.iter_mut().enumerate().take(rows) {
+                    self.push_span(
+                        &mut result,
+                        ty * TILE_SIZE + row,
+                        cursor,
+                        x,
+                        *carry,
+                        fill_rule,
+                    );
+
+                    for column in 0..columns {
+                        let i = row * TILE_SIZE + column;
+
+                        *carry += self.cells[offset + i];
+                        coverage[i] = fill_rule.coverage(*carry);
+                    }
+                }
+
+                result.strips.push(Strip {
+                    x,
+                    y: ty * TILE_SIZE,
+                    coverage,
+                });
+                cursor = x + TILE_SIZE;
+            }
+
+            for (row, carry) in carries.iter().enumerate().take(rows) {
+                let y = ty * TILE_SIZE + row;
+                self.push_span(&mut result, y, cursor, self.width, *carry, fill_rule);
+            }
+        }
+
+        result
+    }
+
+    fn push_span(
+        &self,
+        result: &mut SparseCoverage,
+        y: usize,
+        x0: usize,
+        x1: usize,
+        acc: f32,
+        fill_rule: FillRule,
+    ) {
+        let coverage = fill_rule.coverage(acc);
+
+        if x1 > x0 && coverage > 0.0 {
+            result.spans.push(Span {
+                y,
+                x0,
+                x1,
+                coverage,
+            });
+        }
+    }
+
+    ///
+    /// Outputs an RGBA-encoded buffer like `Rasterizer::render`, only the strips and spans are visited
+    ///
+    pub fn render(&self, fg_color: Color, bg_color: Color, fill_rule: FillRule) -> Vec<f32> {
+        const NUM_CHANNELS: usize = 4;
+        let mut result = vec![0.0_f32; self.width * self.height * NUM_CHANNELS];
+
+        result.chunks_mut(NUM_CHANNELS).for_each(|chunk| {
+            chunk[0] = bg_color.r;
+            chunk[1] = bg_color.g;
+            chunk[2] = bg_color.b;
+            chunk[3] = bg_color.a;
+        });
+
+        let mut blend = |x: usize, y: usize, a: f32| {
+            let i = (y * self.width + x) * NUM_CHANNELS;
+
+            result[i] = fg_color.r * a + bg_color.r * (1.0 - a);
+            result[i + 1] = fg_color.g * a + bg_color.g * (1.0 - a);
+            result[i + 2] = fg_color.b * a + bg_color.b * (1.0 - a);
+        };
+        let coverage = self.coverage(fill_rule);
+
+        for span in coverage.spans.iter() {
+            for x in span.x0..span.x1 {
+                blend(x, span.y, span.coverage);
+            }
+        }
+
+        for strip in coverage.strips.iter() {
+            for row in 0..usize::min(TILE_SIZE, self.height - strip.y) {
+                for column in 0..usize::min(TILE_SIZE, self.width - strip.x) {
+                    blend(
+                        strip.x + column,
+                        strip.y + row,
+                        strip.coverage[row * TILE_SIZE + column],
+                    );
+                }
+            }
+        }
+
+        result
+    }
+}
+
+impl LineSink for SparseRasterizer {
+    fn line(&mut self, p0: Point, p1: Point) {
+        self.draw_line(p0, p1);
+    }
+}
+
+struct TileCells<'a> {
+    tiles: &'a mut HashMap<(usize, usize), usize>,
+    cells: &'a mut Vec<f32>,
+    ///
+    /// Consecutive cells almost always fall in the same tile, so the last lookup is kept
+    ///
+    last: (usize, usize, usize),
+}
+
+impl CellSink for TileCells<'_> {
+    fn add(&mut self, y: usize, x: usize, value: f32) {
+        let (tx, ty) = (x / TILE_SIZE, y / TILE_SIZE);
+
+        if (tx, ty) != (self.last.0, self.last.1) {
+            let cells = &mut *self.cells;
+            let offset = *self.tiles.entry((tx, ty)).or_insert_with(|| {
+                cells.resize(cells.len() + TILE_SIZE * TILE_SIZE, 0.0);
+                cells.len() - TILE_SIZE * TILE_SIZE
+            });
+            self.last = (tx, ty, offset);
+        }
+
+        self.cells[self.last.2 + (y % TILE_SIZE) * TILE_SIZE + x % TILE_SIZE] += value;
+    }
+}
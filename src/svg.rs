@@ -0,0 +1,969 @@
+use crate::blend::BlendMode;
+use crate::canvas::Canvas;
+use crate::flatten::bounded_segments;
+use crate::geometry::Transform;
+use crate::paint::Paint;
+use crate::path::{arc_to, parse_numbers, Path};
+use crate::rasterizer::{FillRule, Rasterizer, SubdivisionMethod};
+use crate::{Color, Point};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineJoin {
+    Miter,
+    Round,
+    Bevel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineCap {
+    Butt,
+    Round,
+    Square,
+}
+
+///
+/// Presentation attributes after inheritance. Group opacity is folded into the
+/// children instead of being composited as a separate layer.
+///
+#[derive(Debug, Clone, Copy)]
+struct Style {
+    transform: Transform,
+    color: Color,
+    fill: Option<Color>,
+    fill_rule: FillRule,
+    fill_opacity: f32,
+    stroke: Option<Color>,
+    stroke_width: f32,
+    stroke_opacity: f32,
+    line_join: LineJoin,
+    line_cap: LineCap,
+    miter_limit: f32,
+    opacity: f32,
+    blend_mode: BlendMode,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            transform: Transform::identity(),
+            color: Color::black(),
+            fill: Some(Color::black()),
+            fill_rule: FillRule::NonZero,
+            fill_opacity: 1.0,
+            stroke: None,
+            stroke_width: 1.0,
+            stroke_opacity: 1.0,
+            line_join: LineJoin::Miter,
+            line_cap: LineCap::Butt,
+            miter_limit: 4.0,
+            opacity: 1.0,
+            blend_mode: BlendMode::Normal,
+        }
+    }
+}
+
+///
+/// Elements whose content is never rendered directly
+///
+const SKIPPED_ELEMENTS: [&str; 14] = [
+    "defs",
+    "clipPath",
+    "mask",
+    "pattern",
+    "symbol",
+    "marker",
+    "linearGradient",
+    "radialGradient",
+    "style",
+    "script",
+    "title",
+    "desc",
+    "metadata",
+    "text",
+];
+
+///
+/// Renders a subset of SVG: `<svg>` with `viewBox`, `<g>`, `<path>`, `<rect>`, `<circle>`,
+/// `<ellipse>`, `<line>`, `<polyline>` and `<polygon>`, with solid fills and strokes.
+/// The tolerance must be a positive number.
+///
+pub fn render_svg(
+    source: &str,
+    tolerance: f32,
+    method: SubdivisionMethod,
+) -> Result<Canvas, String> {
+    if !(tolerance.is_finite() && tolerance > 0.0) {
+        return Err(format!(
+            "Invalid tolerance: {}, it must be a positive number",
+            tolerance
+        ));
+    }
+
+    let mut tokenizer = XmlTokenizer { source, pos: 0 };
+    let mut canvas = None::<Canvas>;
+    let mut styles = vec![Style::default()];
+    let mut skip_depth = 0_usize;
+
+    while let Some(tag) = tokenizer.next_tag()? {
+        let (name, attributes, self_closing) = match tag {
+            XmlTag::End => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                } else if styles.len() > 1 {
+                    styles.pop();
+                }
+                continue;
+            }
+            XmlTag::Start {
+                name,
+                attributes,
+                self_closing,
+            } => (name, attributes, self_closing),
+        };
+
+        if skip_depth > 0 || SKIPPED_ELEMENTS.contains(&name) {
+            if !self_closing {
+                skip_depth += 1;
+            }
+            continue;
+        }
+
+        let attributes = Attributes::new(attributes);
+        let parent = *styles.last().unwrap();
+        let mut style = parent.inherit(&attributes)?;
+
+        if name == "svg" && canvas.is_none() {
+            let (width, height, viewport) = svg_viewport(&attributes)?;
+            style.transform = viewport.then(&style.transform);
+            canvas = Some(Canvas::new(width, height, tolerance, method));
+        } else if let Some(canvas) = canvas.as_mut() {
+            if let Some(path) = shape_to_path(name, &attributes)? {
+                draw_shape(canvas, &path, &style);
+            }
+        } else {
+            return Err(format!("Element <{}> found outside of <svg>", name));
+        }
+
+        if !self_closing {
+            styles.push(style);
+        }
+    }
+
+    canvas.ok_or_else(|| String::from("No <svg> element found"))
+}
+
+fn svg_viewport(attributes: &Attributes) -> Result<(usize, usize, Transform), String> {
+    let view_box = match attributes.get("viewBox") {
+        Some(value) => {
+            let numbers = parse_numbers(value)?;
+
+            if numbers.len() != 4 || numbers[2] <= 0.0 || numbers[3] <= 0.0 {
+                return Err(format!("Invalid viewBox: \"{}\"", value));
+            }
+
+            Some((numbers[0], numbers[1], numbers[2], numbers[3]))
+        }
+        None => None,
+    };
+
+    let size = |key: &str, fallback: f32| -> Result<f32, String> {
+        match attributes.get(key) {
+            Some(value) if !value.trim_end().ends_with('%') => parse_length(value),
+            _ => Ok(fallback),
+        }
+    };
+    let width = size("width", view_box.map_or(100.0, |v| v.2))?;
+    let height = size("height", view_box.map_or(100.0, |v| v.3))?;
+    let (w, h) = (
+        width.ceil().max(1.0) as usize,
+        height.ceil().max(1.0) as usize,
+    );
+
+    let transform = match view_box {
+        // preserveAspectRatio="xMidYMid meet"
+        Some((x, y, vw, vh)) => {
+            let scale = f32::min(width / vw, height / vh);
+            let tx = 0.5 * (width - vw * scale) - x * scale;
+            let ty = 0.5 * (height - vh * scale) - y * scale;
+
+            Transform::translate(tx, ty).then(&Transform::scale(scale, scale))
+        }
+        None => Transform::identity(),
+    };
+
+    Ok((w, h, transform))
+}
+
+impl Style {
+    fn inherit(&self, attributes: &Attributes) -> Result<Style, String> {
+        let mut style = *self;
+
+        style.opacity = 1.0;
+        style.blend_mode = BlendMode::Normal;
+
+        if let Some(value) = attributes.get("color") {
+            if let Some(color) = parse_paint(value, self.color)? {
+                style.color = color;
+            }
+        }
+        if let Some(value) = attributes.get("fill") {
+            style.fill = parse_paint(value, style.color)?;
+        }
+        if let Some(value) = attributes.get("fill-rule") {
+            style.fill_rule = match value {
+                "evenodd" => FillRule::EvenOdd,
+                _ => FillRule::NonZero,
+            };
+        }
+        if let Some(value) = attributes.get("fill-opacity") {
+            style.fill_opacity = parse_opacity(value)?;
+        }
+        if let Some(value) = attributes.get("stroke") {
+            style.stroke = parse_paint(value, style.color)?;
+        }
+        if let Some(value) = attributes.get("stroke-width") {
+            style.stroke_width = parse_length(value)?;
+        }
+        if let Some(value) = attributes.get("stroke-opacity") {
+            style.stroke_opacity = parse_opacity(value)?;
+        }
+        if let Some(value) = attributes.get("stroke-linejoin") {
+            style.line_join = match value {
+                "round" => LineJoin::Round,
+                "bevel" => LineJoin::Bevel,
+                _ => LineJoin::Miter,
+            };
+        }
+        if let Some(value) = attributes.get("stroke-linecap") {
+            style.line_cap = match value {
+                "round" => LineCap::Round,
+                "square" => LineCap::Square,
+                _ => LineCap::Butt,
+            };
+        }
+        if let Some(value) = attributes.get("stroke-miterlimit") {
+            style.miter_limit = parse_number(value)?.max(1.0);
+        }
+        if let Some(value) = attributes.get("opacity") {
+            style.opacity = parse_opacity(value)?;
+        }
+        if let Some(value) = attributes.get("mix-blend-mode") {
+            style.blend_mode = parse_blend_mode(value)?;
+        } else if self.blend_mode != BlendMode::Normal {
+            // Groups are not isolated, the children are blended one by one instead
+            style.blend_mode = self.blend_mode;
+        }
+        if let Some(value) = attributes.get("transform") {
+            style.transform = self.transform.then(&parse_transform(value)?);
+        }
+
+        style.opacity *= self.opacity;
+        Ok(style)
+    }
+}
+
+fn shape_to_path(name: &str, attributes: &Attributes) -> Result<Option<Path>, String> {
+    let number = |key: &str| -> Result<f32, String> {
+        match attributes.get(key) {
+            Some(value) => parse_length(value),
+            None => Ok(0.0),
+        }
+    };
+    let mut path = Path::new();
+
+    match name {
+        "path" => {
+            return match attributes.get("d") {
+                Some(d) => Path::from_svg(d).map(Some),
+                None => Ok(None),
+            }
+        }
+        "rect" => {
+            let (x, y) = (number("x")?, number("y")?);
+            let (w, h) = (number("width")?, number("height")?);
+
+            if w <= 0.0 || h <= 0.0 {
+                return Ok(None);
+            }
+
+            let (mut rx, mut ry) = (number("rx")?, number("ry")?);
+            if attributes.get("rx").is_none() {
+                rx = ry;
+            }
+            if attributes.get("ry").is_none() {
+                ry = rx;
+            }
+            let (rx, ry) = (rx.clamp(0.0, 0.5 * w), ry.clamp(0.0, 0.5 * h));
+
+            if rx > 0.0 && ry > 0.0 {
+                let corner = |path: &mut Path, from: Point, to: Point| {
+                    arc_to(path, from, to, rx, ry, 0.0, false, true);
+                };
+
+                path.move_to(Point { x: x + rx, y });
+                path.line_to(Point { x: x + w - rx, y });
+                corner(
+                    &mut path,
+                    Point { x: x + w - rx, y },
+                    Point {
+                        x: x + w,
+                        y: y + ry,
+                    },
+                );
+                path.line_to(Point {
+                    x: x + w,
+                    y: y + h - ry,
+                });
+                corner(
+                    &mut path,
+                    Point {
+                        x: x + w,
+                        y: y + h - ry,
+                    },
+                    Point {
+                        x: x + w - rx,
+                        y: y + h,
+                    },
+                );
+                path.line_to(Point {
+                    x: x + rx,
+                    y: y + h,
+                });
+                corner(
+                    &mut path,
+                    Point {
+                        x: x + rx,
+                        y: y + h,
+                    },
+                    Point { x, y: y + h - ry },
+                );
+                path.line_to(Point { x, y: y + ry });
+                corner(&mut path, Point { x, y: y + ry }, Point { x: x + rx, y });
+            } else {
+                path.move_to(Point { x, y });
+                path.line_to(Point { x: x + w, y });
+                path.line_to(Point { x: x + w, y: y + h });
+                path.line_to(Point { x, y: y + h });
+            }
+            path.close();
+        }
+        "circle" | "ellipse" => {
+            let (cx, cy) = (number("cx")?, number("cy")?);
+            let (rx, ry) = if name == "circle" {
+                (number("r")?, number("r")?)
+            } else {
+                (number("rx")?, number("ry")?)
+            };
+
+            if rx <= 0.0 || ry <= 0.0 {
+                return Ok(None);
+            }
+
+            let (right, left) = (Point { x: cx + rx, y: cy }, Point { x: cx - rx, y: cy });
+
+            path.move_to(right);
+            arc_to(&mut path, right, left, rx, ry, 0.0, false, true);
+            arc_to(&mut path, left, right, rx, ry, 0.0, false, true);
+            path.close();
+        }
+        "line" => {
+            path.move_to(Point {
+                x: number("x1")?,
+                y: number("y1")?,
+            });
+            path.line_to(Point {
+                x: number("x2")?,
+                y: number("y2")?,
+            });
+        }
+        "polyline" | "polygon" => {
+            let numbers = parse_numbers(attributes.get("points").unwrap_or(""))?;
+            let mut points = numbers.chunks_exact(2).map(|p| Point { x: p[0], y: p[1] });
+
+            match points.next() {
+                Some(p) => path.move_to(p),
+                None => return Ok(None),
+            }
+            points.for_each(|p| path.line_to(p));
+
+            if name == "polygon" {
+                path.close();
+            }
+        }
+        _ => return Ok(None),
+    }
+
+    Ok(Some(path))
+}
+
+fn draw_shape(canvas: &mut Canvas, path: &Path, style: &Style) {
+    let path = path.transform(&style.transform);
+    let with_opacity =
+        |color: Color, opacity: f32| Paint::Solid(color).with_opacity(opacity * style.opacity);
+
+    canvas.blend_mode = style.blend_mode;
+
+    if let Some(fill) = style.fill {
+        canvas.fill_path(
+            &path,
+            &with_opacity(fill, style.fill_opacity),
+            style.fill_rule,
+        );
+    }
+
+    if let Some(stroke) = style.stroke {
+        let half_width = 0.5 * style.stroke_width * style.transform.mean_scale();
+
+        if half_width <= 0.0 {
+            return;
+        }
+
+        let method = canvas.method;
+
+        canvas.fill_with(
+            &with_opacity(stroke, style.stroke_opacity),
+            FillRule::NonZero,
+            |rasterizer| stroke_path(rasterizer, &path, half_width, style, method),
+        );
+    }
+}
+
+///
+/// Strokes are built from one quad per segment plus join and cap polygons, all wound
+/// the same way so that the nonzero rule unions them.
+///
+fn stroke_path(
+    rasterizer: &mut Rasterizer,
+    path: &Path,
+    half_width: f32,
+    style: &Style,
+    method: SubdivisionMethod,
+) {
+    let tolerance = rasterizer.tolerance;
+
+    for polyline in path.flatten(tolerance, method) {
+        let mut points = polyline.points;
+        points.dedup_by(|a, b| a.distance(*b) <= f32::EPSILON);
+
+        if polyline.closed && points.len() > 1 && points[0].distance(points[points.len() - 1]) > 0.0
+        {
+            points.push(points[0]);
+        }
+
+        if points.len() < 2 {
+            if style.line_cap == LineCap::Round && !points.is_empty() {
+                fill_circle(rasterizer, points[0], half_width);
+            }
+            continue;
+        }
+
+        let normal = |a: Point, b: Point| {
+            let length = a.distance(b);
+            Point {
+                x: -(b.y - a.y) / length * half_width,
+                y: (b.x - a.x) / length * half_width,
+            }
+        };
+
+        for segment in points.windows(2) {
+            let (a, b) = (segment[0], segment[1]);
+            let n = normal(a, b);
+
+            fill_polygon(
+                rasterizer,
+                &[
+                    Point {
+                        x: a.x + n.x,
+                        y: a.y + n.y,
+                    },
+                    Point {
+                        x: b.x + n.x,
+                        y: b.y + n.y,
+                    },
+                    Point {
+                        x: b.x - n.x,
+                        y: b.y - n.y,
+                    },
+                    Point {
+                        x: a.x - n.x,
+                        y: a.y - n.y,
+                    },
+                ],
+            );
+        }
+
+        let last = points.len() - 1;
+        let mut joins = (1..last).collect::<Vec<usize>>();
+
+        if polyline.closed && last >= 2 {
+            joins.push(0);
+        }
+
+        for i in joins {
+            let prev = if i == 0 {
+                points[last - 1]
+            } else {
+                points[i - 1]
+            };
+            let (v, next) = (points[i], points[i + 1]);
+            let (n0, n1) = (normal(prev, v), normal(v, next));
+            let turn = (v.x - prev.x) * (next.y - v.y) - (v.y - prev.y) * (next.x - v.x);
+            // The join is filled on the outer side of the turn
+            let side = if turn > 0.0 { -1.0 } else { 1.0 };
+            let a = Point {
+                x: v.x + side * n0.x,
+                y: v.y + side * n0.y,
+            };
+            let b = Point {
+                x: v.x + side * n1.x,
+                y: v.y + side * n1.y,
+            };
+
+            match style.line_join {
+                LineJoin::Round => fill_circle(rasterizer, v, half_width),
+                LineJoin::Bevel => fill_polygon(rasterizer, &[v, a, b]),
+                LineJoin::Miter => {
+                    let mid = Point {
+                        x: n0.x + n1.x,
+                        y: n0.y + n1.y,
+                    };
+                    let cos_half =
+                        (mid.x * n0.x + mid.y * n0.y) / (f32::hypot(mid.x, mid.y) * half_width);
+
+                    if cos_half > 0.0 && cos_half.recip() <= style.miter_limit {
+                        let length = half_width / cos_half / f32::hypot(mid.x, mid.y);
+                        let miter = Point {
+                            x: v.x + side * mid.x * length,
+                            y: v.y + side * mid.y * length,
+                        };
+
+                        fill_polygon(rasterizer, &[v, a, miter, b]);
+                    } else {
+                        fill_polygon(rasterizer, &[v, a, b]);
+                    }
+                }
+            }
+        }
+
+        if !polyline.closed {
+            let caps = [(points[0], points[1]), (points[last], points[last - 1])];
+
+            for (end, inner) in caps {
+                match style.line_cap {
+                    LineCap::Butt => {}
+                    LineCap::Round => fill_circle(rasterizer, end, half_width),
+                    LineCap::Square => {
+                        let n = normal(inner, end);
+                        // Direction along the segment, pointing away from the stroke
+                        let d = Point { x: n.y, y: -n.x };
+
+                        fill_polygon(
+                            rasterizer,
+                            &[
+                                Point {
+                                    x: end.x + n.x,
+                                    y: end.y + n.y,
+                                },
+                                Point {
+                                    x: end.x + n.x + d.x,
+                                    y: end.y + n.y + d.y,
+                                },
+                                Point {
+                                    x: end.x - n.x + d.x,
+                                    y: end.y - n.y + d.y,
+                                },
+                                Point {
+                                    x: end.x - n.x,
+                                    y: end.y - n.y,
+                                },
+                            ],
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+///
+/// Draws a closed polygon, reversed if needed so that all polygons share the same winding
+///
+fn fill_polygon(rasterizer: &mut Rasterizer, points: &[Point]) {
+    let n = points.len();
+    let area = (0..n)
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % n]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>();
+
+    for i in 0..n {
+        let (a, b) = (points[i], points[(i + 1) % n]);
+
+        if area >= 0.0 {
+            rasterizer.draw_line(a, b);
+        } else {
+            rasterizer.draw_line(b, a);
+        }
+    }
+}
+
+fn fill_circle(rasterizer: &mut Rasterizer, center: Point, radius: f32) {
+    let tolerance = rasterizer.tolerance.min(radius);
+    let step = 2.0 * f32::acos(1.0 - tolerance / radius);
+    // A zero tolerance gives a zero step, capped like the segments of curves
+    let n = bounded_segments(2.0 * core::f32::consts::PI / step).max(8);
+
+    let points = (0..n)
+        .map(|i| {
+            let angle = 2.0 * core::f32::consts::PI * i as f32 / n as f32;
+            Point {
+                x: center.x + radius * angle.cos(),
+                y: center.y + radius * angle.sin(),
+            }
+        })
+        .collect::<Vec<Point>>();
+
+    fill_polygon(rasterizer, points.as_slice());
+}
+
+fn parse_number(value: &str) -> Result<f32, String> {
+    value
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| format!("Invalid number: \"{}\"", value))
+}
+
+///
+/// Units are ignored, every length is treated as user space pixels
+///
+fn parse_length(value: &str) -> Result<f32, String> {
+    let value = value.trim();
+    let end = value
+        .find(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E' || c == '%')
+        .unwrap_or(value.len());
+
+    parse_number(&value[..end])
+}
+
+fn parse_opacity(value: &str) -> Result<f32, String> {
+    let value = value.trim();
+
+    match value.strip_suffix('%') {
+        Some(percent) => parse_number(percent).map(|v| (v / 100.0).clamp(0.0, 1.0)),
+        None => parse_number(value).map(|v| v.clamp(0.0, 1.0)),
+    }
+}
+
+fn parse_blend_mode(value: &str) -> Result<BlendMode, String> {
+    Ok(match value.trim() {
+        "normal" => BlendMode::Normal,
+        "multiply" => BlendMode::Multiply,
+        "screen" => BlendMode::Screen,
+        "overlay" => BlendMode::Overlay,
+        "darken" => BlendMode::Darken,
+        "lighten" => BlendMode::Lighten,
+        "color-dodge" => BlendMode::ColorDodge,
+        "color-burn" => BlendMode::ColorBurn,
+        "hard-light" => BlendMode::HardLight,
+        "soft-light" => BlendMode::SoftLight,
+        "difference" => BlendMode::Difference,
+        "exclusion" => BlendMode::Exclusion,
+        _ => return Err(format!("Unsupported blend mode: \"{}\"", value)),
+    })
+}
+
+fn parse_transform(value: &str) -> Result<Transform, String> {
+    let mut transform = Transform::identity();
+    let mut rest = value.trim();
+
+    while !rest.is_empty() {
+        let open = rest
+            .find('(')
+            .ok_or_else(|| format!("Invalid transform: \"{}\"", value))?;
+        let close = rest
+            .find(')')
+            .ok_or_else(|| format!("Invalid transform: \"{}\"", value))?;
+        let name = rest[..open].trim_matches(|c: char| c.is_whitespace() || c == ',');
+        let args = rest[open + 1..close]
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|s| !s.is_empty())
+            .map(parse_number)
+            .collect::<Result<Vec<f32>, String>>()?;
+        let arg = |i: usize, default: f32| args.get(i).copied().unwrap_or(default);
+
+        let current = match (name, args.len()) {
+            ("matrix", 6) => Transform::new(args[0], args[1], args[2], args[3], args[4], args[5]),
+            ("translate", 1..=2) => Transform::translate(arg(0, 0.0), arg(1, 0.0)),
+            ("scale", 1..=2) => Transform::scale(arg(0, 1.0), arg(1, arg(0, 1.0))),
+            ("rotate", 1) => Transform::rotate(arg(0, 0.0).to_radians()),
+            ("rotate", 3) => Transform::translate(arg(1, 0.0), arg(2, 0.0))
+                .then(&Transform::rotate(arg(0, 0.0).to_radians()))
+                .then(&Transform::translate(-arg(1, 0.0), -arg(2, 0.0))),
+            ("skewX", 1) => Transform::skew_x(arg(0, 0.0).to_radians()),
+            ("skewY", 1) => Transform::skew_y(arg(0, 0.0).to_radians()),
+            _ => return Err(format!("Invalid transform: \"{}\"", value)),
+        };
+
+        transform = transform.then(&current);
+        rest = rest[close + 1..].trim_start();
+    }
+
+    Ok(transform)
+}
+
+///
+/// Returns `None` for "none", `current_color` is used for "currentColor"
+///
+fn parse_paint(value: &str, current_color: Color) -> Result<Option<Color>, String> {
+    let value = value.trim();
+    let rgb = |r: f32, g: f32, b: f32| Color {
+        r: r / 255.0,
+        g: g / 255.0,
+        b: b / 255.0,
+        a: 1.0,
+    };
+
+    if value == "none" || value == "transparent" {
+        return Ok(None);
+    }
+    if value == "currentColor" {
+        return Ok(Some(current_color));
+    }
+
+    if let Some(hex) = value.strip_prefix('#') {
+        let digits = hex
+            .chars()
+            .map(|c| c.to_digit(16).map(|d| d as f32))
+            .collect::<Option<Vec<f32>>>()
+            .ok_or_else(|| format!("Invalid color: \"{}\"", value))?;
+
+        return match digits.len() {
+            3 => Ok(Some(rgb(
+                digits[0] * 17.0,
+                digits[1] * 17.0,
+                digits[2] * 17.0,
+            ))),
+            6 => Ok(Some(rgb(
+                digits[0] * 16.0 + digits[1],
+                digits[2] * 16.0 + digits[3],
+                digits[4] * 16.0 + digits[5],
+            ))),
+            _ => Err(format!("Invalid color: \"{}\"", value)),
+        };
+    }
+
+    if let Some(args) = value
+        .strip_prefix("rgb(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let channels = args
+            .split(',')
+            .map(|c| match c.trim().strip_suffix('%') {
+                Some(percent) => parse_number(percent).map(|v| v * 2.55),
+                None => parse_number(c),
+            })
+            .collect::<Result<Vec<f32>, String>>()?;
+
+        return match channels.as_slice() {
+            [r, g, b] => Ok(Some(rgb(
+                r.clamp(0.0, 255.0),
+                g.clamp(0.0, 255.0),
+                b.clamp(0.0, 255.0),
+            ))),
+            _ => Err(format!("Invalid color: \"{}\"", value)),
+        };
+    }
+
+    let named = match value.to_ascii_lowercase().as_str() {
+        "black" => rgb(0.0, 0.0, 0.0),
+        "white" => rgb(255.0, 255.0, 255.0),
+        "red" => rgb(255.0, 0.0, 0.0),
+        "green" => rgb(0.0, 128.0, 0.0),
+        "lime" => rgb(0.0, 255.0, 0.0),
+        "blue" => rgb(0.0, 0.0, 255.0),
+        "yellow" => rgb(255.0, 255.0, 0.0),
+        "cyan" | "aqua" => rgb(0.0, 255.0, 255.0),
+        "magenta" | "fuchsia" => rgb(255.0, 0.0, 255.0),
+        "gray" | "grey" => rgb(128.0, 128.0, 128.0),
+        "silver" => rgb(192.0, 192.0, 192.0),
+        "maroon" => rgb(128.0, 0.0, 0.0),
+        "olive" => rgb(128.0, 128.0, 0.0),
+        "navy" => rgb(0.0, 0.0, 128.0),
+        "purple" => rgb(128.0, 0.0, 128.0),
+        "teal" => rgb(0.0, 128.0, 128.0),
+        "orange" => rgb(255.0, 165.0, 0.0),
+        "yellowgreen" => rgb(154.0, 205.0, 50.0),
+        "steelblue" => rgb(70.0, 130.0, 180.0),
+        _ => return Err(format!("Unsupported color: \"{}\"", value)),
+    };
+
+    Ok(Some(named))
+}
+
+///
+/// Attribute lookup where declarations in `style` take precedence over presentation attributes
+///
+struct Attributes<'a> {
+    attributes: Vec<(&'a str, &'a str)>,
+}
+
+impl<'a> Attributes<'a> {
+    fn new(mut attributes: Vec<(&'a str, &'a str)>) -> Self {
+        if let Some(&(_, style)) = attributes.iter().find(|(key, _)| *key == "style") {
+            for declaration in style.split(';') {
+                if let Some((key, value)) = declaration.split_once(':') {
+                    attributes.push((key.trim(), value.trim()));
+                }
+            }
+        }
+
+        Self { attributes }
+    }
+
+    fn get(&self, key: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .rev()
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| *v)
+    }
+}
+
+enum XmlTag<'a> {
+    Start {
+        name: &'a str,
+        attributes: Vec<(&'a str, &'a str)>,
+        self_closing: bool,
+    },
+    End,
+}
+
+///
+/// Just enough XML to walk the element tree: text, comments, processing instructions
+/// and doctypes are skipped, entities are left as they are.
+///
+struct XmlTokenizer<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> XmlTokenizer<'a> {
+    fn skip_past(&mut self, pattern: &str) -> Result<(), String> {
+        match self.source[self.pos..].find(pattern) {
+            Some(offset) => {
+                self.pos += offset + pattern.len();
+                Ok(())
+            }
+            None => Err(format!("Expected \"{}\" after {}", pattern, self.pos)),
+        }
+    }
+
+    fn next_tag(&mut self) -> Result<Option<XmlTag<'a>>, String> {
+        loop {
+            match self.source[self.pos..].find('<') {
+                Some(offset) => self.pos += offset,
+                None => return Ok(None),
+            }
+
+            let rest = &self.source[self.pos..];
+
+            if rest.starts_with("<!--") {
+                self.skip_past("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                self.skip_past("]]>")?;
+            } else if rest.starts_with("<?") {
+                self.skip_past("?>")?;
+            } else if rest.starts_with("<!") {
+                self.skip_past(">")?;
+            } else if rest.starts_with("</") {
+                self.skip_past(">")?;
+                return Ok(Some(XmlTag::End));
+            } else {
+                return self.start_tag().map(Some);
+            }
+        }
+    }
+
+    fn start_tag(&mut self) -> Result<XmlTag<'a>, String> {
+        let source = self.source;
+        let bytes = source.as_bytes();
+        let is_name_end = |c: u8| c.is_ascii_whitespace() || c == b'/' || c == b'>' || c == b'=';
+
+        self.pos += 1;
+        let start = self.pos;
+        while self.pos < bytes.len() && !is_name_end(bytes[self.pos]) {
+            self.pos += 1;
+        }
+        let name = strip_namespace(&source[start..self.pos]);
+        let mut attributes = Vec::new();
+
+        loop {
+            while self.pos < bytes.len() && bytes[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+
+            match bytes.get(self.pos) {
+                None => return Err(format!("Unterminated <{}>", name)),
+                Some(b'>') => {
+                    self.pos += 1;
+                    return Ok(XmlTag::Start {
+                        name,
+                        attributes,
+                        self_closing: false,
+                    });
+                }
+                Some(b'/') => {
+                    self.skip_past(">")?;
+                    return Ok(XmlTag::Start {
+                        name,
+                        attributes,
+                        self_closing: true,
+                    });
+                }
+                _ => {}
+            }
+
+            let key_start = self.pos;
+            while self.pos < bytes.len() && !is_name_end(bytes[self.pos]) {
+                self.pos += 1;
+            }
+            let key = &source[key_start..self.pos];
+
+            while self.pos < bytes.len() && bytes[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+            if bytes.get(self.pos) != Some(&b'=') {
+                return Err(format!("Expected '=' after attribute \"{}\"", key));
+            }
+            self.pos += 1;
+            while self.pos < bytes.len() && bytes[self.pos].is_ascii_whitespace() {
+                self.pos += 1;
+            }
+
+            let quote = match bytes.get(self.pos) {
+                Some(&q) if q == b'"' || q == b'\'' => q,
+                _ => return Err(format!("Expected a quoted value for \"{}\"", key)),
+            };
+            self.pos += 1;
+            let value_start = self.pos;
+            while self.pos < bytes.len() && bytes[self.pos] != quote {
+                self.pos += 1;
+            }
+            if self.pos >= bytes.len() {
+                return Err(format!("Unterminated value for \"{}\"", key));
+            }
+            let value = &source[value_start..self.pos];
+            self.pos += 1;
+
+            attributes.push((strip_namespace(key), value));
+        }
+    }
+}
+
+fn strip_namespace(name: &str) -> &str {
+    match name.rsplit_once(':') {
+        Some((_, local)) => local,
+        None => name,
+    }
+}
@@ -0,0 +1,58 @@
+//!
+//! Checks of the CSV and JSON reports of the benchmark harness on names that need quoting.
+//!
+
+#![cfg(feature = "std")]
+
+use flattening_quadratic_bezier_curves::bench::{to_csv, to_json, Measurement};
+
+fn measurement(corpus: &str, method: &str) -> Measurement {
+    Measurement {
+        corpus: corpus.to_string(),
+        method: method.to_string(),
+        tolerance: 0.25,
+        curves: 4,
+        nanos_per_curve: 12.0,
+        segments: 10,
+        max_error: 0.5,
+        mean_error: 0.125,
+    }
+}
+
+#[test]
+fn csv_fields_are_quoted() {
+    let csv = to_csv(&[
+        measurement("random", "smart"),
+        measurement("Noto Sans, Bold", "say \"hi\""),
+        measurement("two\nlines", "carriage\rreturn"),
+    ]);
+    let rows = csv.split_terminator('\n').skip(1).collect::<Vec<_>>();
+
+    assert_eq!(rows[0], "random,smart,0.25,4,12.0,10,2.500,0.5,0.125");
+    assert_eq!(
+        rows[1],
+        "\"Noto Sans, Bold\",\"say \"\"hi\"\"\",0.25,4,12.0,10,2.500,0.5,0.125"
+    );
+    // Line breaks stay inside the quoted field
+    assert_eq!(rows[2], "\"two");
+    assert_eq!(
+        rows[3],
+        "lines\",\"carriage\rreturn\",0.25,4,12.0,10,2.500,0.5,0.125"
+    );
+}
+
+#[test]
+fn json_strings_are_escaped() {
+    let mut unbounded = measurement("C:\\fonts\\a.ttf", "tab\there \"quoted\"\n\u{1}é");
+    unbounded.max_error = f32::INFINITY;
+
+    let json = to_json(&[unbounded]);
+
+    assert_eq!(
+        json,
+        "[\n  {\"corpus\": \"C:\\\\fonts\\\\a.ttf\", \
+         \"method\": \"tab\\there \\\"quoted\\\"\\n\\u0001é\", \"tolerance\": 0.25, \
+         \"curves\": 4, \"ns_per_curve\": 12.0, \"segments\": 10, \"mean_segments\": 2.500, \
+         \"max_error\": null, \"mean_error\": 0.125}\n]\n"
+    );
+}
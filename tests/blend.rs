@@ -0,0 +1,260 @@
+//!
+//! Checks of `composite` against the formulas of the W3C Compositing and Blending spec on a few
+//! premultiplied pixels, and of the rasterizer composing its fill the same way as a `Canvas`.
+//! See: https://www.w3.org/TR/compositing-1/
+//!
+
+mod common;
+
+use flattening_quadratic_bezier_curves::blend::{composite, BlendMode, CompositeOp};
+use flattening_quadratic_bezier_curves::paint::Paint;
+use flattening_quadratic_bezier_curves::path::Path;
+use flattening_quadratic_bezier_curves::rasterizer::Blending;
+use flattening_quadratic_bezier_curves::{Color, Rasterizer, SubdivisionMethod};
+
+use common::{assert_pixel, rgba};
+
+const OPS: [CompositeOp; 13] = [
+    CompositeOp::Clear,
+    CompositeOp::Src,
+    CompositeOp::Dst,
+    CompositeOp::SrcOver,
+    CompositeOp::DstOver,
+    CompositeOp::SrcIn,
+    CompositeOp::DstIn,
+    CompositeOp::SrcOut,
+    CompositeOp::DstOut,
+    CompositeOp::SrcAtop,
+    CompositeOp::DstAtop,
+    CompositeOp::Xor,
+    CompositeOp::Plus,
+];
+
+const MODES: [BlendMode; 12] = [
+    BlendMode::Normal,
+    BlendMode::Multiply,
+    BlendMode::Screen,
+    BlendMode::Overlay,
+    BlendMode::Darken,
+    BlendMode::Lighten,
+    BlendMode::ColorDodge,
+    BlendMode::ColorBurn,
+    BlendMode::HardLight,
+    BlendMode::SoftLight,
+    BlendMode::Difference,
+    BlendMode::Exclusion,
+];
+
+///
+/// Straight RGBA source and backdrop pairs, with opaque, translucent, transparent and extreme
+/// components
+///
+const PIXELS: [([f32; 4], [f32; 4]); 5] = [
+    ([0.8, 0.4, 0.1, 1.0], [0.2, 0.6, 0.9, 1.0]),
+    ([0.8, 0.4, 0.1, 0.5], [0.2, 0.6, 0.9, 0.75]),
+    ([1.0, 0.0, 0.7, 0.25], [0.0, 1.0, 0.3, 0.6]),
+    ([0.3, 0.9, 0.5, 0.0], [0.6, 0.2, 0.1, 0.4]),
+    ([0.6, 0.55, 0.2, 0.9], [0.6, 0.2, 0.1, 0.0]),
+];
+
+fn premultiply(color: [f32; 4]) -> [f32; 4] {
+    [
+        color[0] * color[3],
+        color[1] * color[3],
+        color[2] * color[3],
+        color[3],
+    ]
+}
+
+///
+/// Fa and Fb of the Porter-Duff table
+///
+fn factors(op: CompositeOp, a_s: f32, a_b: f32) -> (f32, f32) {
+    match op {
+        CompositeOp::Clear => (0.0, 0.0),
+        CompositeOp::Src => (1.0, 0.0),
+        CompositeOp::Dst => (0.0, 1.0),
+        CompositeOp::SrcOver => (1.0, 1.0 - a_s),
+        CompositeOp::DstOver => (1.0 - a_b, 1.0),
+        CompositeOp::SrcIn => (a_b, 0.0),
+        CompositeOp::DstIn => (0.0, a_s),
+        CompositeOp::SrcOut => (1.0 - a_b, 0.0),
+        CompositeOp::DstOut => (0.0, 1.0 - a_s),
+        CompositeOp::SrcAtop => (a_b, 1.0 - a_s),
+        CompositeOp::DstAtop => (1.0 - a_b, a_s),
+        CompositeOp::Xor => (1.0 - a_b, 1.0 - a_s),
+        CompositeOp::Plus => (1.0, 1.0),
+    }
+}
+
+///
+/// B(Cb, Cs) as written in the spec, each mode on its own
+///
+fn blend(mode: BlendMode, cb: f32, cs: f32) -> f32 {
+    let hard_light = |cb: f32, cs: f32| {
+        if cs <= 0.5 {
+            cb * 2.0 * cs
+        } else {
+            let cs = 2.0 * cs - 1.0;
+            cb + cs - cb * cs
+        }
+    };
+
+    match mode {
+        BlendMode::Normal => cs,
+        BlendMode::Multiply => cb * cs,
+        BlendMode::Screen => cb + cs - cb * cs,
+        BlendMode::Overlay => hard_light(cs, cb),
+        BlendMode::Darken => cb.min(cs),
+        BlendMode::Lighten => cb.max(cs),
+        BlendMode::ColorDodge => {
+            if cb == 0.0 {
+                0.0
+            } else if cs == 1.0 {
+                1.0
+            } else {
+                (cb / (1.0 - cs)).min(1.0)
+            }
+        }
+        BlendMode::ColorBurn => {
+            if cb == 1.0 {
+                1.0
+            } else if cs == 0.0 {
+                0.0
+            } else {
+                1.0 - ((1.0 - cb) / cs).min(1.0)
+            }
+        }
+        BlendMode::HardLight => hard_light(cb, cs),
+        BlendMode::SoftLight => {
+            if cs <= 0.5 {
+                cb - (1.0 - 2.0 * cs) * cb * (1.0 - cb)
+            } else {
+                let d = if cb <= 0.25 {
+                    ((16.0 * cb - 12.0) * cb + 4.0) * cb
+                } else {
+                    cb.sqrt()
+                };
+                cb + (2.0 * cs - 1.0) * (d - cb)
+            }
+        }
+        BlendMode::Difference => (cb - cs).abs(),
+        BlendMode::Exclusion => cb + cs - 2.0 * cb * cs,
+    }
+}
+
+#[test]
+fn porter_duff_operators() {
+    for op in OPS {
+        for (source, backdrop) in PIXELS {
+            let (a_s, a_b) = (source[3], backdrop[3]);
+            let (fa, fb) = factors(op, a_s, a_b);
+            let (s, b) = (premultiply(source), premultiply(backdrop));
+            // co = cs × Fa + cb × Fb and αo = αs × Fa + αb × Fb, on premultiplied colors
+            let mut expected = [0.0; 4];
+
+            for i in 0..4 {
+                expected[i] = s[i] * fa + b[i] * fb;
+                if op == CompositeOp::Plus {
+                    expected[i] = expected[i].min(1.0);
+                }
+            }
+
+            assert_pixel(
+                composite(s, b, op, BlendMode::Normal),
+                expected,
+                format!("{:?} of {:?} onto {:?}", op, source, backdrop).as_str(),
+            );
+        }
+    }
+}
+
+#[test]
+fn separable_blend_modes() {
+    for mode in MODES {
+        for (source, backdrop) in PIXELS {
+            let (a_s, a_b) = (source[3], backdrop[3]);
+            // Cs = (1 - αb) × Cs + αb × B(Cb, Cs), then source-over
+            let mut expected = [0.0; 4];
+
+            for i in 0..3 {
+                let cs = (1.0 - a_b) * source[i] + a_b * blend(mode, backdrop[i], source[i]);
+
+                expected[i] = a_s * cs + a_b * backdrop[i] * (1.0 - a_s);
+            }
+            expected[3] = a_s + a_b * (1.0 - a_s);
+
+            assert_pixel(
+                composite(
+                    premultiply(source),
+                    premultiply(backdrop),
+                    CompositeOp::SrcOver,
+                    mode,
+                ),
+                expected,
+                format!("{:?} of {:?} onto {:?}", mode, source, backdrop).as_str(),
+            );
+        }
+    }
+}
+
+#[test]
+fn render_composites_the_fill() {
+    let mut rasterizer = Rasterizer {
+        width: 20,
+        height: 10,
+        accumulation_buffer: vec![0.0; 20 * 10],
+        tolerance: 0.25,
+    };
+    let path = Path::from_svg("M0 0 H10.5 V10 H0 Z").unwrap();
+
+    rasterizer.draw_path(&path, SubdivisionMethod::ParabolaApprox);
+
+    let fg = rgba(0.9, 0.3, 0.5, 0.8);
+    let bg = rgba(0.2, 0.6, 0.4, 0.7);
+    let straight = |c: Color| [c.r, c.g, c.b, c.a];
+
+    for op in [CompositeOp::SrcOver, CompositeOp::Xor, CompositeOp::DstOut] {
+        for mode in [BlendMode::Normal, BlendMode::Multiply, BlendMode::SoftLight] {
+            let image =
+                rasterizer.render_composited(&Paint::Solid(fg), bg, Blending::Srgb, op, mode);
+            #[cfg(feature = "std")]
+            assert_eq!(
+                rasterizer.render_parallel_composited(
+                    &Paint::Solid(fg),
+                    bg,
+                    Blending::Srgb,
+                    op,
+                    mode,
+                    3
+                ),
+                image
+            );
+
+            let b = premultiply(straight(bg));
+            let full = composite(premultiply(straight(fg)), b, op, mode);
+
+            // Fully covered, half covered and uncovered pixels, back to straight alpha
+            for (x, coverage) in [(5, 1.0), (10, 0.5), (15, 0.0)] {
+                let mixed: [f32; 4] = core::array::from_fn(|i| b[i] + (full[i] - b[i]) * coverage);
+                let expected = if mixed[3] > 0.0 {
+                    [
+                        mixed[0] / mixed[3],
+                        mixed[1] / mixed[3],
+                        mixed[2] / mixed[3],
+                        mixed[3],
+                    ]
+                } else {
+                    [0.0; 4]
+                };
+                let i = (5 * 20 + x) * 4;
+
+                assert_pixel(
+                    [image[i], image[i + 1], image[i + 2], image[i + 3]],
+                    expected,
+                    format!("{:?} {:?} at {}", op, mode, x).as_str(),
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,135 @@
+//!
+//! Checks of the `Canvas` state: fills composite in order with their own paint, the coverage
+//! clips the composition and the pixels are kept premultiplied.
+//!
+
+#![cfg(feature = "std")]
+
+mod common;
+
+use flattening_quadratic_bezier_curves::canvas::Canvas;
+use flattening_quadratic_bezier_curves::paint::Paint;
+use flattening_quadratic_bezier_curves::path::Path;
+use flattening_quadratic_bezier_curves::rasterizer::{Blending, FillRule};
+use flattening_quadratic_bezier_curves::{Rasterizer, SubdivisionMethod};
+
+use common::{assert_pixel, pixel, rgba};
+
+fn rect(x0: f32, y0: f32, x1: f32, y1: f32) -> Path {
+    Path::from_svg(format!("M{} {} H{} V{} H{} Z", x0, y0, x1, y1, x0).as_str()).unwrap()
+}
+
+fn canvas() -> Canvas {
+    Canvas::new(20, 10, 0.25, SubdivisionMethod::ParabolaApprox)
+}
+
+#[test]
+fn fills_composite_in_order() {
+    let mut canvas = canvas();
+
+    assert!(canvas.pixels.iter().all(|value| *value == 0.0));
+
+    canvas.clear(rgba(1.0, 1.0, 1.0, 1.0));
+    canvas.fill_path(
+        &rect(0.0, 0.0, 10.0, 10.0),
+        &Paint::Solid(rgba(1.0, 0.0, 0.0, 1.0)),
+        FillRule::NonZero,
+    );
+    canvas.fill_path(
+        &rect(5.0, 0.0, 15.0, 10.0),
+        &Paint::Solid(rgba(0.0, 0.0, 1.0, 0.5)),
+        FillRule::NonZero,
+    );
+
+    assert_pixel(pixel(&canvas, 2, 5), [1.0, 0.0, 0.0, 1.0], "first fill");
+    assert_pixel(
+        pixel(&canvas, 7, 5),
+        [0.5, 0.0, 0.5, 1.0],
+        "second over first",
+    );
+    assert_pixel(
+        pixel(&canvas, 12, 5),
+        [0.5, 0.5, 1.0, 1.0],
+        "second over white",
+    );
+    // Outside of both shapes the clear color is untouched
+    assert_pixel(pixel(&canvas, 17, 5), [1.0, 1.0, 1.0, 1.0], "outside");
+}
+
+#[test]
+fn coverage_scales_the_composition() {
+    let mut canvas = canvas();
+
+    canvas.clear(rgba(0.0, 0.0, 0.0, 1.0));
+    canvas.fill_path(
+        &rect(0.0, 0.0, 10.25, 10.0),
+        &Paint::Solid(rgba(1.0, 1.0, 1.0, 1.0)),
+        FillRule::NonZero,
+    );
+
+    assert_pixel(
+        pixel(&canvas, 10, 5),
+        [0.25, 0.25, 0.25, 1.0],
+        "partial pixel",
+    );
+}
+
+#[test]
+fn pixels_are_premultiplied() {
+    let mut canvas = canvas();
+    let color = rgba(0.2, 0.4, 0.8, 0.5);
+
+    canvas.fill_path(
+        &rect(0.0, 0.0, 20.0, 10.0),
+        &Paint::Solid(color),
+        FillRule::NonZero,
+    );
+
+    assert_pixel(pixel(&canvas, 3, 3), [0.1, 0.2, 0.4, 0.5], "premultiplied");
+
+    let straight = canvas.to_rgba();
+    assert_pixel(
+        [straight[0], straight[1], straight[2], straight[3]],
+        [0.2, 0.4, 0.8, 0.5],
+        "to_rgba",
+    );
+}
+
+#[test]
+fn contrast_compares_with_the_straight_destination() {
+    let mut canvas = canvas();
+
+    canvas.blending = Blending::Linear { contrast: 1.0 };
+    canvas.clear(rgba(1.0, 1.0, 1.0, 0.5));
+    canvas.fill_path(
+        &rect(0.0, 0.0, 10.25, 10.0),
+        &Paint::Solid(rgba(0.0, 0.0, 0.0, 1.0)),
+        FillRule::NonZero,
+    );
+
+    // Black over white darkens the coverage of 0.25 to 0.25^(1/2) whatever the opacity of the
+    // white, so the alpha goes halfway from 0.5 to 1
+    assert!((pixel(&canvas, 10, 5)[3] - 0.75).abs() <= 1e-5);
+}
+
+#[test]
+fn fill_with_uses_the_fill_rule() {
+    let mut canvas = canvas();
+    let white = Paint::Solid(rgba(1.0, 1.0, 1.0, 1.0));
+    let twice = |rasterizer: &mut Rasterizer| {
+        rasterizer.draw_path(
+            &rect(0.0, 0.0, 10.0, 10.0),
+            SubdivisionMethod::ParabolaApprox,
+        );
+        rasterizer.draw_path(
+            &rect(0.0, 0.0, 10.0, 10.0),
+            SubdivisionMethod::ParabolaApprox,
+        );
+    };
+
+    canvas.fill_with(&white, FillRule::EvenOdd, twice);
+    assert_pixel(pixel(&canvas, 5, 5), [0.0; 4], "even-odd");
+
+    canvas.fill_with(&white, FillRule::NonZero, twice);
+    assert_pixel(pixel(&canvas, 5, 5), [1.0; 4], "nonzero");
+}
@@ -0,0 +1,108 @@
+//!
+//! Checks of the sRGB transfer functions and of the linear light blending of `render`.
+//!
+
+use flattening_quadratic_bezier_curves::color::{linear_to_srgb, srgb_to_linear};
+use flattening_quadratic_bezier_curves::path::Path;
+use flattening_quadratic_bezier_curves::rasterizer::Blending;
+use flattening_quadratic_bezier_curves::{Color, Rasterizer, SubdivisionMethod};
+
+fn gray(value: f32) -> Color {
+    Color {
+        r: value,
+        g: value,
+        b: value,
+        a: 1.0,
+    }
+}
+
+#[test]
+fn transfer_functions_round_trip() {
+    let mut previous = -1.0;
+
+    for i in 0..=255 {
+        let value = i as f32 / 255.0;
+        let linear = srgb_to_linear(value);
+
+        assert!(linear > previous, "not increasing at {}", value);
+        assert!(
+            (linear_to_srgb(linear) - value).abs() <= 1e-5,
+            "{} comes back as {}",
+            value,
+            linear_to_srgb(linear)
+        );
+        previous = linear;
+    }
+
+    assert_eq!(srgb_to_linear(0.0), 0.0);
+    assert!((srgb_to_linear(1.0) - 1.0).abs() <= 1e-6);
+    assert!((srgb_to_linear(0.5) - 0.214_041).abs() <= 1e-5);
+
+    let color = Color {
+        r: 0.1,
+        g: 0.5,
+        b: 0.9,
+        a: 0.3,
+    };
+    let back = color.to_linear().to_srgb();
+
+    assert_eq!(color.to_linear().a, 0.3);
+    assert!([back.r - 0.1, back.g - 0.5, back.b - 0.9, back.a - 0.3]
+        .iter()
+        .all(|d| d.abs() <= 1e-5));
+}
+
+#[test]
+fn contrast_adjusts_partial_coverage_only() {
+    let (dark, light) = (gray(0.0), gray(1.0));
+    let contrast = Blending::Linear { contrast: 0.5 };
+
+    for coverage in [0.0, 0.25, 0.5, 0.75, 1.0] {
+        assert_eq!(
+            Blending::Srgb.adjust_coverage(coverage, dark, light),
+            coverage
+        );
+        assert_eq!(
+            Blending::Linear { contrast: 0.0 }.adjust_coverage(coverage, dark, light),
+            coverage
+        );
+    }
+
+    assert_eq!(contrast.adjust_coverage(0.0, dark, light), 0.0);
+    assert_eq!(contrast.adjust_coverage(1.0, dark, light), 1.0);
+    // Dark on light thickens, light on dark thins, equal luminances are left alone
+    assert!(contrast.adjust_coverage(0.5, dark, light) > 0.5);
+    assert!(contrast.adjust_coverage(0.5, light, dark) < 0.5);
+    assert_eq!(contrast.adjust_coverage(0.5, light, light), 0.5);
+}
+
+#[test]
+fn linear_blending_mixes_in_linear_light() {
+    let mut rasterizer = Rasterizer {
+        width: 4,
+        height: 1,
+        accumulation_buffer: vec![0.0; 4],
+        tolerance: 0.25,
+    };
+    let (fg, bg) = (gray(0.8), gray(0.2));
+
+    rasterizer.draw_path(
+        &Path::from_svg("M0 0 H1.5 V1 H0 Z").unwrap(),
+        SubdivisionMethod::ParabolaApprox,
+    );
+
+    let image = rasterizer.render(fg, bg);
+    let linear = rasterizer.render_with_paint(&fg.into(), bg, Blending::Linear { contrast: 0.0 });
+    let half = linear_to_srgb(0.5 * (srgb_to_linear(0.8) + srgb_to_linear(0.2)));
+
+    // Covered and uncovered pixels come back to the sRGB colors unchanged
+    for (x, expected) in [(0, 0.8), (2, 0.2), (3, 0.2)] {
+        assert!((linear[x * 4] - expected).abs() <= 1e-5, "pixel {}", x);
+        assert_eq!(linear[x * 4 + 3], 1.0);
+    }
+
+    assert!((image[4] - 0.5).abs() <= 1e-5);
+    assert!((linear[4] - half).abs() <= 1e-5);
+    // The linear mix of the half covered pixel is lighter than the sRGB one
+    assert!(linear[4] > image[4] + 0.05);
+}
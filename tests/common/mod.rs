@@ -0,0 +1,45 @@
+//!
+//! Helpers shared by the integration tests, each test crate only uses some of them.
+//!
+
+#![allow(dead_code)]
+
+#[cfg(feature = "std")]
+use flattening_quadratic_bezier_curves::canvas::Canvas;
+use flattening_quadratic_bezier_curves::Color;
+
+pub fn rgba(r: f32, g: f32, b: f32, a: f32) -> Color {
+    Color { r, g, b, a }
+}
+
+///
+/// Premultiplied RGBA of the pixel at `x`, `y`
+///
+#[cfg(feature = "std")]
+pub fn pixel(canvas: &Canvas, x: usize, y: usize) -> [f32; 4] {
+    let i = (y * canvas.width + x) * 4;
+    let p = &canvas.pixels[i..i + 4];
+
+    [p[0], p[1], p[2], p[3]]
+}
+
+pub fn assert_pixel(actual: [f32; 4], expected: [f32; 4], context: &str) {
+    assert!(
+        actual
+            .iter()
+            .zip(expected)
+            .all(|(a, e)| (a - e).abs() <= 1e-5),
+        "{}: {:?} instead of {:?}",
+        context,
+        actual,
+        expected
+    );
+}
+
+pub fn assert_color(actual: Color, expected: Color, context: &str) {
+    assert_pixel(
+        [actual.r, actual.g, actual.b, actual.a],
+        [expected.r, expected.g, expected.b, expected.a],
+        context,
+    );
+}
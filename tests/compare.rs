@@ -0,0 +1,75 @@
+//!
+//! Checks of the statistics and heatmap of `compare_renders`.
+//!
+
+#![cfg(feature = "std")]
+
+use flattening_quadratic_bezier_curves::compare::compare_renders;
+use flattening_quadratic_bezier_curves::path::Path;
+use flattening_quadratic_bezier_curves::rasterizer::f32_to_u8;
+use flattening_quadratic_bezier_curves::{Color, Rasterizer, SubdivisionMethod};
+
+fn render(method: SubdivisionMethod, tolerance: f32) -> Vec<f32> {
+    let mut rasterizer = Rasterizer {
+        width: 40,
+        height: 30,
+        accumulation_buffer: vec![0.0; 40 * 30],
+        tolerance,
+    };
+
+    rasterizer.draw_path(
+        &Path::from_svg("M20 2 A13 13 0 0 1 20 28 A13 13 0 0 1 20 2 Z").unwrap(),
+        method,
+    );
+    rasterizer.render(Color::black(), Color::white())
+}
+
+#[test]
+fn identical_images_have_an_infinite_psnr() {
+    let image = render(SubdivisionMethod::ParabolaApprox, 0.25);
+    let diff = compare_renders(&image, &image, 40, 30, 100.0);
+
+    assert_eq!(diff.psnr, f64::INFINITY);
+    assert_eq!(diff.max_difference, 0.0);
+    assert_eq!(diff.differing_pixels, 0);
+    assert_eq!(diff.heatmap.len(), 40 * 30 * 4);
+    assert!(diff.heatmap.chunks(4).all(|pixel| pixel == [0, 0, 0, 255]));
+}
+
+#[test]
+fn differences_are_measured_per_channel() {
+    let a = vec![0.5_f32; 2 * 2 * 4];
+    let mut b = a.clone();
+
+    // One visible difference and one below 1/255 that only the heatmap shows
+    b[4] += 0.1;
+    b[9] += 0.001;
+
+    let diff = compare_renders(&a, &b, 2, 2, 20.0);
+    let mse = (0.1_f64 * 0.1 + 0.001 * 0.001) / 12.0;
+
+    assert!((diff.psnr - 10.0 * (1.0 / mse).log10()).abs() <= 1e-3);
+    assert!((diff.max_difference - 0.1).abs() <= 1e-6);
+    assert_eq!(diff.differing_pixels, 1);
+    assert_eq!(&diff.heatmap[0..4], [0, 0, 0, 255]);
+    // Amplified to 2.0 and 0.02: saturated white, then a dark red
+    let white = f32_to_u8(1.0);
+    assert_eq!(&diff.heatmap[4..8], [white, white, white, 255]);
+    assert!(diff.heatmap[8] > 0 && diff.heatmap[9] == 0);
+    assert_eq!(&diff.heatmap[12..16], [0, 0, 0, 255]);
+}
+
+#[test]
+fn methods_render_almost_the_same() {
+    let smart = render(SubdivisionMethod::ParabolaApprox, 0.1);
+    let recursive = render(SubdivisionMethod::DeCasteljau, 0.1);
+    let coarse = render(SubdivisionMethod::ParabolaApprox, 2.0);
+    let close = compare_renders(&smart, &recursive, 40, 30, 1.0);
+    let far = compare_renders(&smart, &coarse, 40, 30, 1.0);
+
+    assert!(close.psnr.is_finite() && close.psnr > 30.0);
+    assert!(close.max_difference < 0.1);
+    // A looser tolerance moves the edges further
+    assert!(far.psnr < close.psnr);
+    assert!(far.differing_pixels > close.differing_pixels);
+}
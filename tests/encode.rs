@@ -0,0 +1,118 @@
+//!
+//! Byte level checks of the built-in writers of `encode`
+//!
+
+#![cfg(feature = "std")]
+
+use flattening_quadratic_bezier_curves::encode::{encode, Format, Pixels};
+use flattening_quadratic_bezier_curves::path::Path;
+use flattening_quadratic_bezier_curves::rasterizer::FillRule;
+use flattening_quadratic_bezier_curves::{Point, Rasterizer, SubdivisionMethod};
+
+const RGBA: [u8; 8] = [255, 0, 10, 255, 1, 2, 3, 128];
+
+fn header_and_data(bytes: &[u8], header_lines: usize) -> (String, &[u8]) {
+    let mut end = 0;
+
+    for _ in 0..header_lines {
+        end += bytes[end..].iter().position(|byte| *byte == b'\n').unwrap() + 1;
+    }
+
+    (
+        String::from_utf8(bytes[..end].to_vec()).unwrap(),
+        &bytes[end..],
+    )
+}
+
+#[test]
+fn ppm_drops_alpha() {
+    let bytes = encode(Pixels::Rgba8(&RGBA), 2, 1, Format::Ppm).unwrap();
+    let (header, data) = header_and_data(&bytes, 3);
+
+    assert_eq!(header, "P6\n2 1\n255\n");
+    assert_eq!(data, &[255, 0, 10, 1, 2, 3]);
+}
+
+#[test]
+fn plain_formats_are_text_rows() {
+    let ppm = encode(Pixels::Rgba8(&RGBA), 1, 2, Format::PlainPpm).unwrap();
+    let pgm = encode(Pixels::Alpha16(&[0, 65535, 7, 8]), 2, 2, Format::PlainPgm).unwrap();
+
+    assert_eq!(
+        String::from_utf8(ppm).unwrap(),
+        "P3\n1 2\n255\n255 0 10\n1 2 3\n"
+    );
+    assert_eq!(
+        String::from_utf8(pgm).unwrap(),
+        "P2\n2 2\n65535\n0 65535\n7 8\n"
+    );
+}
+
+#[test]
+fn sixteen_bit_byte_order() {
+    let samples = [0x0102, 0xfffe];
+    let pgm = encode(Pixels::Alpha16(&samples), 2, 1, Format::Pgm).unwrap();
+    let raw = encode(Pixels::Alpha16(&samples), 2, 1, Format::Raw).unwrap();
+
+    // Netpbm is big endian, raw dumps little endian
+    assert_eq!(header_and_data(&pgm, 3).1, &[0x01, 0x02, 0xff, 0xfe]);
+    assert_eq!(raw, vec![0x02, 0x01, 0xfe, 0xff]);
+}
+
+#[test]
+fn pam_keeps_alpha() {
+    let bytes = encode(Pixels::Rgba8(&RGBA), 2, 1, Format::Pam).unwrap();
+    let (header, data) = header_and_data(&bytes, 7);
+
+    assert_eq!(
+        header,
+        "P7\nWIDTH 2\nHEIGHT 1\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n"
+    );
+    assert_eq!(data, &RGBA);
+}
+
+#[test]
+fn invalid_inputs_are_errors() {
+    assert!(encode(Pixels::Rgba8(&RGBA), 3, 1, Format::Raw).is_err());
+    assert!(encode(Pixels::Rgba8(&RGBA), 2, 1, Format::Pgm).is_err());
+    assert_eq!(Format::from_path("mask.PGM"), Some(Format::Pgm));
+    assert_eq!(Format::from_path("mask"), None);
+}
+
+#[test]
+fn mask_round_trips_through_pgm() {
+    let point = |x, y| Point { x, y };
+    let mut path = Path::new();
+    path.move_to(point(4.0, 4.0));
+    path.quad_to(point(30.0, 2.0), point(28.0, 28.0));
+    path.line_to(point(3.0, 20.0));
+    path.close();
+
+    let mut rasterizer = Rasterizer {
+        width: 32,
+        height: 32,
+        accumulation_buffer: vec![0.0; 32 * 32],
+        tolerance: 0.25,
+    };
+    rasterizer.draw_path(&path, SubdivisionMethod::ParabolaApprox);
+
+    let mask = rasterizer.coverage_mask_u8(FillRule::NonZero);
+    let bytes = encode(Pixels::Alpha8(&mask), 32, 32, Format::Pgm).unwrap();
+
+    assert_eq!(header_and_data(&bytes, 3).1, mask.as_slice());
+}
+
+#[cfg(feature = "image")]
+#[test]
+fn png_decodes_to_the_same_pixels() {
+    let mask = [0_u16, 1, 0x0102, 65535];
+    let bytes = encode(Pixels::Alpha16(&mask), 2, 2, Format::Png).unwrap();
+    let decoded = image::load_from_memory(&bytes).unwrap().to_luma16();
+
+    assert_eq!(decoded.as_raw().as_slice(), &mask);
+
+    let bytes = encode(Pixels::Rgba8(&RGBA), 2, 1, Format::Png).unwrap();
+    let decoded = image::load_from_memory(&bytes).unwrap().to_rgba8();
+
+    assert_eq!(decoded.as_raw().as_slice(), &RGBA);
+}
@@ -0,0 +1,249 @@
+//!
+//! Re-renders the reference images of `docs/` and compares them with the committed ones.
+//!
+//! On mismatch the output and a diff image are written to `target/golden-diff/`.
+//! `BLESS=1 cargo test --test golden` overwrites the goldens with the current output instead.
+//!
+
+#![cfg(all(feature = "font", feature = "image"))]
+
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use flattening_quadratic_bezier_curves::font::{glyph_test, RendererColors};
+use flattening_quadratic_bezier_curves::rasterizer::f32_to_u8;
+use flattening_quadratic_bezier_curves::{
+    Color, Point, QuadraticBezier, Rasterizer, SubdivisionMethod,
+};
+
+///
+/// Largest accepted difference on any channel of any pixel
+///
+const TOLERANCE: u8 = 1;
+
+struct Rendered {
+    name: String,
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+fn render(rasterizer: &Rasterizer, name: &str, colors: RendererColors) -> Rendered {
+    Rendered {
+        name: name.to_string(),
+        width: rasterizer.width,
+        height: rasterizer.height,
+        pixels: rasterizer
+            .render(colors.fg_color, colors.bg_color)
+            .iter()
+            .map(|value| f32_to_u8(*value))
+            .collect(),
+    }
+}
+
+fn project_path(relative: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(relative)
+}
+
+fn save(path: &PathBuf, width: usize, height: usize, pixels: &[u8]) {
+    image::save_buffer(
+        path,
+        pixels,
+        width as u32,
+        height as u32,
+        image::ColorType::Rgba8,
+    )
+    .unwrap();
+}
+
+///
+/// Compares with `docs/<name>`, returns a description of the mismatch if there is one
+///
+fn compare_with_golden(rendered: &Rendered) -> Option<String> {
+    let golden_path = project_path("docs").join(&rendered.name);
+
+    if std::env::var_os("BLESS").is_some() {
+        save(
+            &golden_path,
+            rendered.width,
+            rendered.height,
+            &rendered.pixels,
+        );
+        return None;
+    }
+
+    let golden = match image::open(&golden_path) {
+        Ok(golden) => golden.to_rgba8(),
+        Err(error) => return Some(format!("{}: {}", rendered.name, error)),
+    };
+    let diff_dir = project_path("target/golden-diff");
+    std::fs::create_dir_all(&diff_dir).unwrap();
+
+    if golden.dimensions() != (rendered.width as u32, rendered.height as u32) {
+        save(
+            &diff_dir.join(&rendered.name),
+            rendered.width,
+            rendered.height,
+            &rendered.pixels,
+        );
+        return Some(format!(
+            "{}: size is {}x{}, golden is {:?}",
+            rendered.name,
+            rendered.width,
+            rendered.height,
+            golden.dimensions()
+        ));
+    }
+
+    let mut diff = Vec::<u8>::with_capacity(rendered.pixels.len());
+    let (mut max_difference, mut differing) = (0_u8, 0_usize);
+
+    for (actual, expected) in rendered
+        .pixels
+        .chunks(4)
+        .zip(golden.as_raw().as_slice().chunks(4))
+    {
+        let difference = actual
+            .iter()
+            .zip(expected)
+            .map(|(a, e)| a.abs_diff(*e))
+            .max()
+            .unwrap_or(0);
+
+        max_difference = max_difference.max(difference);
+
+        if difference > TOLERANCE {
+            differing += 1;
+            // Mismatches in red, from dim to bright with the size of the difference
+            diff.extend_from_slice(&[127 + difference / 2, 0, 0, 255]);
+        } else {
+            // Faded golden, for context
+            let gray = (expected[0] as u16 + expected[1] as u16 + expected[2] as u16) / 12 + 170;
+            diff.extend_from_slice(&[gray as u8, gray as u8, gray as u8, 255]);
+        }
+    }
+
+    if differing == 0 {
+        return None;
+    }
+
+    save(
+        &diff_dir.join(&rendered.name),
+        rendered.width,
+        rendered.height,
+        &rendered.pixels,
+    );
+    save(
+        &diff_dir.join(format!("diff_{}", rendered.name)),
+        rendered.width,
+        rendered.height,
+        &diff,
+    );
+
+    Some(format!(
+        "{}: {} pixels differ by more than {}, up to {}, see {}",
+        rendered.name,
+        differing,
+        TOLERANCE,
+        max_difference,
+        diff_dir.display()
+    ))
+}
+
+fn assert_goldens(rendered: Vec<Rendered>) {
+    assert!(!rendered.is_empty());
+
+    let failures = rendered
+        .iter()
+        .filter_map(compare_with_golden)
+        .collect::<Vec<String>>();
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+fn glyph_goldens(font: &str, glyph_index: u16, tolerance: f32, colors: RendererColors) {
+    let rendered = RefCell::new(Vec::<Rendered>::new());
+
+    glyph_test(
+        project_path(font).to_str().unwrap(),
+        glyph_index,
+        tolerance,
+        colors,
+        |rasterizer, name, colors| rendered.borrow_mut().push(render(rasterizer, name, colors)),
+    );
+
+    assert_goldens(rendered.into_inner());
+}
+
+#[test]
+fn simple_quadratic() {
+    let quadratic = QuadraticBezier::new(
+        Point { x: 100.0, y: 400.0 },
+        Point { x: 300.0, y: 400.0 },
+        Point { x: 500.0, y: 100.0 },
+    );
+    let colors = RendererColors {
+        fg_color: Color::black(),
+        bg_color: Color::white(),
+    };
+    let rendered = [
+        ("smart", SubdivisionMethod::ParabolaApprox),
+        ("recursive", SubdivisionMethod::DeCasteljau),
+    ]
+    .into_iter()
+    .map(|(name, method)| {
+        let mut rasterizer = Rasterizer {
+            tolerance: 0.25,
+            ..Default::default()
+        };
+
+        rasterizer.draw_quadratic(quadratic, method);
+        render(
+            &rasterizer,
+            format!("{}_subdivision_simple_test.png", name).as_str(),
+            colors,
+        )
+    })
+    .collect();
+
+    assert_goldens(rendered);
+}
+
+#[test]
+fn glyph_at_sign() {
+    glyph_goldens(
+        "media/Roboto-MediumItalic.ttf",
+        36,
+        0.25,
+        RendererColors {
+            fg_color: Color::black(),
+            bg_color: Color::white(),
+        },
+    );
+}
+
+#[test]
+fn glyph_f() {
+    glyph_goldens(
+        "media/Jfwildwood-ldYZ.ttf",
+        42,
+        1.5,
+        RendererColors {
+            fg_color: Color::white(),
+            bg_color: Color::yellow_green(),
+        },
+    );
+}
+
+#[test]
+fn glyph_w() {
+    glyph_goldens(
+        "media/Jfwildwood-ldYZ.ttf",
+        59,
+        2.5,
+        RendererColors {
+            fg_color: Color::white(),
+            bg_color: Color::steel_blue(),
+        },
+    );
+}
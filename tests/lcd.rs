@@ -0,0 +1,114 @@
+//!
+//! Checks of the LCD renderer against the grayscale one at the same geometry.
+//!
+
+#![cfg(feature = "std")]
+
+use flattening_quadratic_bezier_curves::lcd::{LcdRasterizer, SubpixelOrder, DEFAULT_LCD_FILTER};
+use flattening_quadratic_bezier_curves::path::Path;
+use flattening_quadratic_bezier_curves::rasterizer::FillRule;
+use flattening_quadratic_bezier_curves::{Color, Rasterizer, SubdivisionMethod};
+
+const WIDTH: usize = 24;
+const HEIGHT: usize = 16;
+
+///
+/// A circle and a rectangle with edges in the middle of pixels
+///
+fn path() -> Path {
+    Path::from_svg("M12 2 A6 6 0 0 1 12 14 A6 6 0 0 1 12 2 Z M1.5 3.25 H5.75 V12.5 H1.5 Z").unwrap()
+}
+
+fn grayscale() -> Rasterizer {
+    let mut rasterizer = Rasterizer {
+        width: WIDTH,
+        height: HEIGHT,
+        accumulation_buffer: vec![0.0; WIDTH * HEIGHT],
+        tolerance: 0.001,
+    };
+
+    rasterizer.draw_path(&path(), SubdivisionMethod::ParabolaApprox);
+    rasterizer
+}
+
+fn lcd(order: SubpixelOrder, filter: [f32; 5]) -> LcdRasterizer {
+    let mut rasterizer = LcdRasterizer::new(WIDTH, HEIGHT, 0.001, order);
+
+    rasterizer.filter = filter;
+    rasterizer.draw_path(&path(), SubdivisionMethod::ParabolaApprox);
+    rasterizer
+}
+
+#[test]
+fn unfiltered_subpixels_average_to_the_grayscale_coverage() {
+    let expected = grayscale().coverage_mask(FillRule::NonZero);
+
+    for order in [
+        SubpixelOrder::Rgb,
+        SubpixelOrder::Bgr,
+        SubpixelOrder::VRgb,
+        SubpixelOrder::VBgr,
+    ] {
+        let coverage = lcd(order, [0.0, 0.0, 1.0, 0.0, 0.0]).coverage();
+
+        for (i, (channels, expected)) in coverage.iter().zip(&expected).enumerate() {
+            let mean = channels.iter().sum::<f32>() / 3.0;
+
+            assert!(
+                (mean - expected).abs() <= 2e-3,
+                "{:?} pixel {}: {} instead of {}",
+                order,
+                i,
+                mean,
+                expected
+            );
+        }
+    }
+}
+
+#[test]
+fn filtering_keeps_the_total_coverage() {
+    let expected = grayscale()
+        .coverage_mask(FillRule::NonZero)
+        .iter()
+        .sum::<f32>();
+    let coverage = lcd(SubpixelOrder::Rgb, DEFAULT_LCD_FILTER).coverage();
+    let total = coverage.iter().flatten().sum::<f32>() / 3.0;
+
+    // The shapes stay away from the borders, where the filter would lose coverage
+    assert!((total - expected).abs() <= 1e-2 * expected);
+}
+
+#[test]
+fn render_matches_grayscale_where_channels_agree() {
+    let (fg, bg) = (Color::white(), Color::steel_blue());
+    let gray = grayscale().render(fg, bg);
+    let lcd = lcd(SubpixelOrder::Rgb, DEFAULT_LCD_FILTER);
+    let coverage = lcd.coverage();
+    let image = lcd.render(fg, bg);
+    let mut agreeing = 0;
+
+    for (i, channels) in coverage.iter().enumerate() {
+        // Fully covered or empty along the filter footprint
+        if channels.iter().all(|c| *c == channels[0]) {
+            agreeing += 1;
+            assert!(
+                (0..4).all(|c| (image[i * 4 + c] - gray[i * 4 + c]).abs() <= 1e-5),
+                "pixel {}: {:?} instead of {:?}",
+                i,
+                &image[i * 4..i * 4 + 4],
+                &gray[i * 4..i * 4 + 4]
+            );
+        }
+
+        // Each channel mixes with its own coverage
+        for c in 0..3 {
+            let (f, b) = ([fg.r, fg.g, fg.b][c], [bg.r, bg.g, bg.b][c]);
+
+            assert!((image[i * 4 + c] - (b + (f - b) * channels[c])).abs() <= 1e-5);
+        }
+        assert_eq!(image[i * 4 + 3], 1.0);
+    }
+
+    assert!(agreeing > WIDTH * HEIGHT / 2);
+}
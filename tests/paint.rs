@@ -0,0 +1,136 @@
+//!
+//! Checks of the gradient and pattern paints sampled through their transform, and of the
+//! validation of pattern data.
+//!
+
+mod common;
+
+use flattening_quadratic_bezier_curves::geometry::Transform;
+use flattening_quadratic_bezier_curves::paint::{
+    ColorStop, Filter, Gradient, GradientKind, Pattern, Spread,
+};
+use flattening_quadratic_bezier_curves::Point;
+
+use common::{assert_color, rgba};
+
+///
+/// Black to white from x = 0 to x = 10 in gradient space
+///
+fn linear_gradient(transform: Transform) -> Gradient {
+    Gradient::new(
+        GradientKind::Linear {
+            start: Point { x: 0.0, y: 0.0 },
+            end: Point { x: 10.0, y: 0.0 },
+        },
+        vec![
+            ColorStop {
+                offset: 0.0,
+                color: rgba(0.0, 0.0, 0.0, 1.0),
+            },
+            ColorStop {
+                offset: 1.0,
+                color: rgba(1.0, 1.0, 1.0, 1.0),
+            },
+        ],
+        Spread::Pad,
+        transform,
+    )
+}
+
+#[test]
+fn gradients_sample_through_the_inverse_transform() {
+    let mut gradient = linear_gradient(Transform::identity());
+
+    assert_color(
+        gradient.color_at(2.5, 7.0),
+        rgba(0.25, 0.25, 0.25, 1.0),
+        "identity",
+    );
+
+    // Stretched to 40 canvas units and shifted by 20: x = 40 is halfway
+    let transform = Transform::translate(20.0, 0.0).then(&Transform::scale(4.0, 1.0));
+    gradient.set_transform(transform);
+
+    assert_eq!(gradient.transform(), transform);
+    assert_color(
+        gradient.color_at(40.0, 3.0),
+        rgba(0.5, 0.5, 0.5, 1.0),
+        "set_transform",
+    );
+    assert_color(
+        gradient.color_at(10.0, 3.0),
+        rgba(0.0, 0.0, 0.0, 1.0),
+        "padded",
+    );
+}
+
+#[test]
+fn singular_gradient_transforms_are_transparent() {
+    let transparent = rgba(0.0, 0.0, 0.0, 0.0);
+    let mut gradient = linear_gradient(Transform::scale(0.0, 1.0));
+
+    for (x, y) in [(0.0, 0.0), (2.5, 1.0), (100.0, -4.0)] {
+        assert_color(gradient.color_at(x, y), transparent, "built singular");
+    }
+
+    gradient.set_transform(Transform::identity());
+    assert_color(
+        gradient.color_at(5.0, 0.0),
+        rgba(0.5, 0.5, 0.5, 1.0),
+        "made invertible",
+    );
+
+    gradient.set_transform(Transform::new(1.0, 2.0, 2.0, 4.0, 0.0, 0.0));
+    assert_color(gradient.color_at(5.0, 0.0), transparent, "set singular");
+}
+
+///
+/// 2x1 image, opaque red then half transparent blue
+///
+fn pattern() -> Pattern {
+    let mut pattern =
+        Pattern::from_rgba8(2, 1, &[255, 0, 0, 255, 0, 0, 255, 128]).expect("valid pattern");
+
+    pattern.filter = Filter::Nearest;
+    pattern
+}
+
+#[test]
+fn patterns_sample_through_the_inverse_transform() {
+    let mut pattern = pattern();
+    let red = rgba(1.0, 0.0, 0.0, 1.0);
+    let blue = rgba(0.0, 0.0, 1.0, 128.0 / 255.0);
+
+    assert_color(pattern.color_at(0.5, 0.5), red, "identity");
+    assert_color(pattern.color_at(1.5, 0.5), blue, "identity");
+
+    // Texels 10 canvas units wide, starting at x = 100
+    let transform = Transform::translate(100.0, 0.0).then(&Transform::scale(10.0, 10.0));
+    pattern.set_transform(transform);
+
+    assert_eq!(pattern.transform(), transform);
+    assert_color(pattern.color_at(105.0, 5.0), red, "set_transform");
+    assert_color(pattern.color_at(115.0, 5.0), blue, "set_transform");
+    // Repeated by default
+    assert_color(pattern.color_at(125.0, 15.0), red, "repeated");
+}
+
+#[test]
+fn singular_pattern_transforms_are_transparent() {
+    let mut pattern = pattern();
+
+    pattern.set_transform(Transform::scale(1.0, 0.0));
+
+    for (x, y) in [(0.5, 0.5), (1.5, 0.0), (-3.0, 8.0)] {
+        assert_color(pattern.color_at(x, y), rgba(0.0, 0.0, 0.0, 0.0), "singular");
+    }
+}
+
+#[test]
+fn pattern_data_must_match_its_size() {
+    assert!(Pattern::from_rgba8(2, 2, &[0; 16]).is_ok());
+    assert!(Pattern::from_rgba8(0, 5, &[]).is_ok());
+    assert!(Pattern::from_rgba8(2, 2, &[0; 15]).is_err());
+    assert!(Pattern::from_rgba8(2, 2, &[0; 20]).is_err());
+    assert!(Pattern::from_rgba8(usize::MAX, 2, &[0; 8]).is_err());
+}
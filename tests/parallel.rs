@@ -0,0 +1,130 @@
+//!
+//! Checks that the banded accumulation and resolve give the same result as the serial ones,
+//! bit for bit, for any number of bands and on empty canvases.
+//!
+
+#![cfg(feature = "std")]
+
+use flattening_quadratic_bezier_curves::paint::Paint;
+use flattening_quadratic_bezier_curves::rasterizer::Blending;
+use flattening_quadratic_bezier_curves::{Color, Point, Rasterizer};
+
+const SIZES: [(usize, usize); 6] = [(64, 48), (7, 3), (1, 1), (0, 10), (10, 0), (0, 0)];
+const BANDS: [usize; 6] = [0, 1, 2, 3, 7, 1000];
+
+///
+/// Lines spread over and around a `width` x `height` canvas, some of them crossing y = 0 and
+/// leaving it, with a few degenerate ones
+///
+fn lines(width: usize, height: usize) -> Vec<(Point, Point)> {
+    let mut state = 17_u64;
+    let mut next = |min: f32, max: f32| {
+        state = state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        min + ((state >> 40) as f32 / (1_u64 << 24) as f32) * (max - min)
+    };
+    let (w, h) = (width as f32, height as f32);
+    let mut lines = (0..200)
+        .map(|_| {
+            (
+                Point {
+                    x: next(-10.0, w + 10.0),
+                    y: next(-10.0, h + 10.0),
+                },
+                Point {
+                    x: next(-10.0, w + 10.0),
+                    y: next(-10.0, h + 10.0),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    lines.extend([
+        (Point { x: 0.5, y: -3.0 }, Point { x: 2.5, y: 3.0 }),
+        (Point { x: 1.0, y: 1.0 }, Point { x: 1.0, y: 1.0 }),
+        (
+            Point {
+                x: f32::NAN,
+                y: 0.0,
+            },
+            Point { x: 1.0, y: 2.0 },
+        ),
+    ]);
+    lines
+}
+
+fn rasterizer(width: usize, height: usize) -> Rasterizer {
+    Rasterizer {
+        width,
+        height,
+        accumulation_buffer: vec![0.0; width * height],
+        tolerance: 0.25,
+    }
+}
+
+fn serial(width: usize, height: usize) -> Rasterizer {
+    let mut rasterizer = rasterizer(width, height);
+
+    for (p0, p1) in lines(width, height) {
+        rasterizer.draw_line(p0, p1);
+    }
+    rasterizer
+}
+
+#[test]
+fn parallel_accumulation_matches_serial() {
+    for (width, height) in SIZES {
+        let expected = serial(width, height);
+
+        for bands in BANDS {
+            let mut parallel = rasterizer(width, height);
+
+            parallel.draw_lines_parallel(lines(width, height).as_slice(), bands);
+
+            assert!(
+                parallel
+                    .accumulation_buffer
+                    .iter()
+                    .zip(&expected.accumulation_buffer)
+                    .all(|(a, b)| a.to_bits() == b.to_bits()),
+                "{}x{} in {} bands",
+                width,
+                height,
+                bands
+            );
+        }
+    }
+}
+
+#[test]
+fn parallel_render_matches_serial() {
+    let (fg, bg) = (Color::white(), Color::steel_blue());
+
+    for (width, height) in SIZES {
+        let rasterizer = serial(width, height);
+
+        for blending in [Blending::Srgb, Blending::Linear { contrast: 0.5 }] {
+            let expected = rasterizer.render_with_paint(&Paint::Solid(fg), bg, blending);
+
+            assert_eq!(expected.len(), width * height * 4);
+
+            for bands in BANDS {
+                let parallel = rasterizer.render_parallel(&Paint::Solid(fg), bg, blending, bands);
+
+                assert!(
+                    parallel.len() == expected.len()
+                        && parallel
+                            .iter()
+                            .zip(&expected)
+                            .all(|(a, b)| a.to_bits() == b.to_bits()),
+                    "{}x{} in {} bands with {:?}",
+                    width,
+                    height,
+                    bands,
+                    blending
+                );
+            }
+        }
+    }
+}
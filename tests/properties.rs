@@ -0,0 +1,1128 @@
+//!
+//! Randomized checks of the geometry invariants. Every case comes from a fixed seed so that
+//! a failure always reproduces, with the case number in the assertion message.
+//!
+
+use std::f32::consts::PI;
+
+#[cfg(feature = "std")]
+use flattening_quadratic_bezier_curves::batch::QuadraticBatch;
+#[cfg(feature = "std")]
+use flattening_quadratic_bezier_curves::bench::flattening_error;
+use flattening_quadratic_bezier_curves::flatten::{
+    ClosingSink, DeCasteljau, DeCasteljauPoints, Flattener, ParabolaApprox, ParabolaPoints,
+    MAX_SEGMENTS,
+};
+use flattening_quadratic_bezier_curves::path::{arc_to, cubic_to_quadratics, Path, PathCommand};
+use flattening_quadratic_bezier_curves::rasterizer::FillRule;
+#[cfg(feature = "std")]
+use flattening_quadratic_bezier_curves::reference::supersample;
+use flattening_quadratic_bezier_curves::{Point, QuadraticBezier, Rasterizer, SubdivisionMethod};
+
+const CASES: u64 = 500;
+
+///
+/// Knuth's MMIX linear congruential generator, enough to spread the cases around
+///
+struct Random {
+    state: u64,
+}
+
+impl Random {
+    fn new(seed: u64) -> Self {
+        let mut random = Random { state: seed };
+        random.next_u64();
+        random
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.state
+    }
+
+    ///
+    /// Uniform in [min, max)
+    ///
+    fn range(&mut self, min: f32, max: f32) -> f32 {
+        let unit = (self.next_u64() >> 40) as f32 / (1_u64 << 24) as f32;
+        min + unit * (max - min)
+    }
+
+    fn point(&mut self, min: f32, max: f32) -> Point {
+        Point {
+            x: self.range(min, max),
+            y: self.range(min, max),
+        }
+    }
+
+    ///
+    /// Mostly generic curves, with some nearly straight and cusped ones mixed in since those
+    /// are the edge cases of the parabola approximation
+    ///
+    fn quadratic(&mut self) -> QuadraticBezier {
+        let (p0, p2) = (self.point(0.0, 500.0), self.point(0.0, 500.0));
+        let p1 = match self.next_u64() % 4 {
+            // Control point on the far side of the chord from both ends: cusp-like
+            0 => p0.lerp(p2, self.range(1.5, 4.0)),
+            // Control point close to the chord: almost a line
+            1 => {
+                let p = p0.lerp(p2, self.range(0.0, 1.0));
+                Point {
+                    x: p.x + self.range(-0.5, 0.5),
+                    y: p.y + self.range(-0.5, 0.5),
+                }
+            }
+            _ => self.point(0.0, 500.0),
+        };
+
+        QuadraticBezier::new(p0, p1, p2)
+    }
+}
+
+fn assert_close(a: Point, b: Point, epsilon: f32, context: &str) {
+    assert!(
+        a.distance(b) <= epsilon,
+        "{}: {:?} and {:?} are {} apart",
+        context,
+        a,
+        b,
+        a.distance(b)
+    );
+}
+
+#[test]
+fn full_subsegment_is_the_curve() {
+    let mut random = Random::new(1);
+
+    for case in 0..CASES {
+        let q = random.quadratic();
+        let sub = q.subsegment(0.0, 1.0);
+
+        for (a, b) in sub.points().iter().zip(q.points().iter()) {
+            assert_close(*a, *b, 1e-3, format!("case {}", case).as_str());
+        }
+    }
+}
+
+#[test]
+fn subsegment_matches_parent() {
+    let mut random = Random::new(2);
+
+    for case in 0..CASES {
+        let q = random.quadratic();
+        let t0 = random.range(0.0, 1.0);
+        let t1 = random.range(0.0, 1.0);
+        let sub = q.subsegment(t0, t1);
+
+        for k in 0..=8 {
+            let s = k as f32 / 8.0;
+
+            assert_close(
+                sub.eval(s),
+                q.eval(t0 + s * (t1 - t0)),
+                1e-2,
+                format!("case {}, t0 {} t1 {} s {}", case, t0, t1, s).as_str(),
+            );
+        }
+    }
+}
+
+#[test]
+fn subdivisions_are_monotonic() {
+    let mut random = Random::new(3);
+
+    for case in 0..CASES {
+        let q = random.quadratic();
+        let tolerance = random.range(0.05, 2.0);
+
+        for (name, ts) in [
+            ("recursive", q.recursive_subdivide(tolerance)),
+            ("smart", q.smart_subdivide(tolerance)),
+        ] {
+            let context = format!("case {}, {} at {}: {:?}", case, name, tolerance, ts);
+
+            assert!(ts.len() >= 2, "{}", context);
+            assert_eq!(ts[0], 0.0, "{}", context);
+            assert_eq!(ts[ts.len() - 1], 1.0, "{}", context);
+            assert!(ts.windows(2).all(|w| w[0] < w[1]), "{}", context);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn batches_match_smart_subdivide() {
+    let mut random = Random::new(14);
+    let mut curves = (0..CASES).map(|_| random.quadratic()).collect::<Vec<_>>();
+    let p = random.point(0.0, 500.0);
+
+    // Collinear with the control point inside and outside of the chord, and a single point
+    curves.push(QuadraticBezier::new(
+        p,
+        p.lerp(curves[0].eval(1.0), 0.3),
+        curves[0].eval(1.0),
+    ));
+    curves.push(QuadraticBezier::new(
+        p,
+        p.lerp(curves[1].eval(1.0), 2.5),
+        curves[1].eval(1.0),
+    ));
+    curves.push(QuadraticBezier::new(p, p, p));
+
+    let mut batch = QuadraticBatch::new();
+    for q in curves.iter() {
+        batch.push(*q);
+    }
+
+    for tolerance in [0.05, 0.25, 1.0] {
+        let (mut points, mut offsets) = (Vec::new(), Vec::new());
+        let (mut points_8, mut offsets_8) = (Vec::new(), Vec::new());
+
+        batch.flatten::<4>(tolerance, &mut points, &mut offsets);
+        batch.flatten::<8>(tolerance, &mut points_8, &mut offsets_8);
+
+        assert_eq!(offsets.len(), curves.len() + 1);
+        assert_eq!(offsets, offsets_8);
+
+        for (case, q) in curves.iter().enumerate() {
+            let ts = q.smart_subdivide(tolerance);
+            let range = offsets[case]..offsets[case + 1];
+            let context = format!("case {} at {}: {:?}", case, tolerance, q);
+
+            assert_eq!(range.len(), ts.len(), "{}", context);
+
+            for ((t, a), b) in ts.iter().zip(&points[range.clone()]).zip(&points_8[range]) {
+                assert_close(*a, q.eval(*t), 8e-5, context.as_str());
+                assert_close(*b, q.eval(*t), 8e-5, context.as_str());
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn polylines_stay_within_tolerance() {
+    let mut random = Random::new(4);
+    // The parabola approximation and the sampled error are both estimates, a few percent over
+    // the tolerance is expected, a wrong segment count is not
+    let slack = 1.1;
+
+    for case in 0..CASES {
+        let q = random.quadratic();
+        let tolerance = random.range(0.05, 2.0);
+
+        for method in [
+            SubdivisionMethod::DeCasteljau,
+            SubdivisionMethod::ParabolaApprox,
+            SubdivisionMethod::Wang,
+            SubdivisionMethod::ForwardDifferencing,
+        ] {
+            let points = q.flatten(tolerance, method).collect::<Vec<Point>>();
+            let error = flattening_error(&q, points.as_slice());
+
+            assert!(
+                error <= tolerance * slack,
+                "case {}, {} at {}: error {} with {} points for {:?}",
+                case,
+                method.name(),
+                tolerance,
+                error,
+                points.len(),
+                q
+            );
+        }
+    }
+}
+
+fn coverage_sum(rasterizer: &Rasterizer) -> f32 {
+    let mut sum = 0.0_f64;
+
+    for row in rasterizer.accumulation_buffer.chunks(rasterizer.width) {
+        let mut acc = 0.0_f32;
+
+        for cell in row {
+            acc += cell;
+            sum += FillRule::NonZero.coverage(acc) as f64;
+        }
+    }
+
+    sum as f32
+}
+
+///
+/// Star shaped around a point of the canvas. One vertex per angular sector keeps the gaps
+/// between vertices under half a turn, so that the polygon never crosses itself.
+///
+fn random_polygon(random: &mut Random) -> Path {
+    let center = random.point(96.0, 160.0);
+    let sides = 5 + (random.next_u64() % 12) as usize;
+    let mut path = Path::new();
+
+    for i in 0..sides {
+        let angle = (i as f32 + random.range(0.0, 1.0)) * 2.0 * PI / sides as f32;
+        let radius = random.range(5.0, 90.0);
+        let p = Point {
+            x: center.x + radius * angle.cos(),
+            y: center.y + radius * angle.sin(),
+        };
+
+        if i == 0 {
+            path.move_to(p);
+        } else {
+            path.line_to(p);
+        }
+    }
+
+    path.close();
+    path
+}
+
+///
+/// Rotated ellipse made of quadratics by `arc_to`, returns the path and its perimeter
+///
+fn random_ellipse(random: &mut Random) -> (Path, f32) {
+    let center = random.point(96.0, 160.0);
+    let (rx, ry) = (random.range(3.0, 90.0), random.range(3.0, 90.0));
+    let rotation = random.range(0.0, PI);
+    let (sin, cos) = rotation.sin_cos();
+    let start = Point {
+        x: center.x + rx * cos,
+        y: center.y + rx * sin,
+    };
+    let opposite = Point {
+        x: center.x - rx * cos,
+        y: center.y - rx * sin,
+    };
+    let mut path = Path::new();
+
+    path.move_to(start);
+    arc_to(&mut path, start, opposite, rx, ry, rotation, false, true);
+    arc_to(&mut path, opposite, start, rx, ry, rotation, false, true);
+    path.close();
+
+    // Ramanujan's approximation, only used to bound the flattening error
+    let h = ((rx - ry) / (rx + ry)).powi(2);
+    let perimeter = PI * (rx + ry) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()));
+
+    (path, perimeter)
+}
+
+#[test]
+fn polygon_coverage_is_its_area() {
+    let mut random = Random::new(5);
+
+    for case in 0..CASES {
+        let path = random_polygon(&mut random);
+        let mut rasterizer = Rasterizer {
+            width: 256,
+            height: 256,
+            accumulation_buffer: vec![0.0; 256 * 256],
+            ..Default::default()
+        };
+
+        rasterizer.draw_path(&path, SubdivisionMethod::ParabolaApprox);
+
+        let (coverage, area) = (coverage_sum(&rasterizer), path.signed_area().abs());
+
+        assert!(
+            (coverage - area).abs() <= 1e-3 * area.max(1.0),
+            "case {}: coverage {}, area {}",
+            case,
+            coverage,
+            area
+        );
+    }
+}
+
+#[test]
+fn curved_coverage_is_its_area() {
+    let mut random = Random::new(6);
+
+    for case in 0..CASES {
+        let (path, perimeter) = random_ellipse(&mut random);
+        let area = path.signed_area().abs();
+
+        for method in [
+            SubdivisionMethod::DeCasteljau,
+            SubdivisionMethod::ParabolaApprox,
+        ] {
+            let mut rasterizer = Rasterizer {
+                width: 256,
+                height: 256,
+                accumulation_buffer: vec![0.0; 256 * 256],
+                tolerance: 0.1,
+            };
+
+            rasterizer.draw_path(&path, method);
+
+            let coverage = coverage_sum(&rasterizer);
+
+            // The polyline cuts inside every curve by at most the tolerance
+            assert!(
+                (coverage - area).abs() <= perimeter * rasterizer.tolerance + 1e-3 * area,
+                "case {}, {}: coverage {}, area {}",
+                case,
+                method.name(),
+                coverage,
+                area
+            );
+        }
+    }
+}
+
+fn cubic_eval(p0: Point, p1: Point, p2: Point, p3: Point, t: f32) -> Point {
+    let mt = 1.0 - t;
+    let (w0, w1, w2, w3) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+
+    Point {
+        x: w0 * p0.x + w1 * p1.x + w2 * p2.x + w3 * p3.x,
+        y: w0 * p0.y + w1 * p1.y + w2 * p2.y + w3 * p3.y,
+    }
+}
+
+///
+/// Shoelace formula over densely sampled curves, as a reference for `Path::signed_area`
+///
+fn sampled_area(path: &Path) -> f64 {
+    const SAMPLES: usize = 1024;
+
+    let mut points = Vec::<Vec<Point>>::new();
+    let mut last = Point { x: 0.0, y: 0.0 };
+
+    for command in path.commands.iter() {
+        match *command {
+            PathCommand::MoveTo(p) => points.push(vec![p]),
+            PathCommand::LineTo(p) => points.last_mut().unwrap().push(p),
+            PathCommand::QuadTo(p1, p) => {
+                let q = QuadraticBezier::new(last, p1, p);
+                let subpath = points.last_mut().unwrap();
+
+                subpath.extend((1..=SAMPLES).map(|k| q.eval(k as f32 / SAMPLES as f32)));
+            }
+            PathCommand::CubicTo(p1, p2, p) => {
+                let subpath = points.last_mut().unwrap();
+
+                subpath.extend(
+                    (1..=SAMPLES).map(|k| cubic_eval(last, p1, p2, p, k as f32 / SAMPLES as f32)),
+                );
+            }
+            PathCommand::Close => {}
+        }
+
+        last = match *command {
+            PathCommand::MoveTo(p)
+            | PathCommand::LineTo(p)
+            | PathCommand::QuadTo(_, p)
+            | PathCommand::CubicTo(_, _, p) => p,
+            PathCommand::Close => points.last().unwrap()[0],
+        };
+    }
+
+    points
+        .iter()
+        .map(|subpath| {
+            let n = subpath.len();
+
+            (0..n)
+                .map(|i| {
+                    let (a, b) = (subpath[i], subpath[(i + 1) % n]);
+                    a.x as f64 * b.y as f64 - a.y as f64 * b.x as f64
+                })
+                .sum::<f64>()
+                * 0.5
+        })
+        .sum()
+}
+
+///
+/// Any mix of segments, self-intersections and several subpaths included
+///
+fn random_path(random: &mut Random, min: f32, max: f32) -> Path {
+    let mut path = Path::new();
+
+    for _ in 0..1 + random.next_u64() % 3 {
+        path.move_to(random.point(min, max));
+
+        for _ in 0..1 + random.next_u64() % 6 {
+            match random.next_u64() % 3 {
+                0 => path.line_to(random.point(min, max)),
+                1 => path.quad_to(random.point(min, max), random.point(min, max)),
+                _ => path.cubic_to(
+                    random.point(min, max),
+                    random.point(min, max),
+                    random.point(min, max),
+                ),
+            }
+        }
+
+        if random.next_u64().is_multiple_of(2) {
+            path.close();
+        }
+    }
+
+    path
+}
+
+#[test]
+fn signed_area_is_exact_for_curves() {
+    let mut random = Random::new(7);
+
+    for case in 0..CASES {
+        let path = random_path(&mut random, 0.0, 500.0);
+        let (exact, sampled) = (path.signed_area() as f64, sampled_area(&path));
+
+        assert!(
+            (exact - sampled).abs() <= 1.0 + 1e-4 * sampled.abs(),
+            "case {}: signed area {}, sampled {} for {:?}",
+            case,
+            exact,
+            sampled,
+            path
+        );
+    }
+}
+
+///
+/// Random polygon snapped to a grid of `step` pixels, so that the vertices and edges land on
+/// pixel boundaries, the edge cases of the cell splitting
+///
+fn random_snapped_polygon(random: &mut Random, step: f32) -> Path {
+    let mut path = Path::new();
+    let snapped = |random: &mut Random| {
+        let p = random.point(1.0, 255.0);
+
+        Point {
+            x: (p.x / step).round() * step,
+            y: (p.y / step).round() * step,
+        }
+    };
+
+    path.move_to(snapped(random));
+
+    for _ in 0..2 + random.next_u64() % 10 {
+        let p = snapped(random);
+
+        path.line_to(p);
+
+        // Axis aligned edges now and then
+        if random.next_u64().is_multiple_of(4) {
+            let q = snapped(random);
+            path.line_to(Point { x: p.x, y: q.y });
+        }
+    }
+
+    path.close();
+    path
+}
+
+///
+/// Length of the flattened path, and the sum over its segments of the area between them and
+/// the right edge of the canvas. The latter is what the accumulation buffer adds up before
+/// the lobes of opposite signs cancel, so the rounding error grows with it.
+///
+fn flattened_magnitudes(path: &Path, rasterizer: &Rasterizer) -> (f32, f32) {
+    let (mut length, mut magnitude) = (0.0_f32, 0.0_f32);
+
+    for polyline in path.flatten(rasterizer.tolerance, SubdivisionMethod::ParabolaApprox) {
+        let n = polyline.points.len();
+
+        for i in 0..n {
+            let (a, b) = (polyline.points[i], polyline.points[(i + 1) % n]);
+
+            length += a.distance(b);
+            magnitude += (rasterizer.width as f32 - 0.5 * (a.x + b.x)) * (b.y - a.y).abs();
+        }
+    }
+
+    (length, magnitude)
+}
+
+#[test]
+fn accumulation_conserves_polygon_area() {
+    let mut random = Random::new(8);
+
+    for case in 0..CASES {
+        let path = match case % 3 {
+            0 => random_snapped_polygon(&mut random, 1.0),
+            1 => random_snapped_polygon(&mut random, 0.5),
+            _ => random_path(&mut random, 1.0, 255.0),
+        };
+        let mut rasterizer = Rasterizer {
+            width: 256,
+            height: 256,
+            accumulation_buffer: vec![0.0; 256 * 256],
+            tolerance: 0.05,
+        };
+
+        rasterizer.draw_path(&path, SubdivisionMethod::ParabolaApprox);
+
+        let (accumulated, exact) = (rasterizer.signed_area(), path.signed_area());
+        let (length, magnitude) = flattened_magnitudes(&path, &rasterizer);
+        // Polygons are only off by the f32 rounding, curves also by the flattening error
+        let allowed = if case % 3 == 2 {
+            1e-5 * magnitude + length * rasterizer.tolerance
+        } else {
+            1e-5 * magnitude
+        };
+
+        assert!(
+            (accumulated - exact).abs() <= allowed,
+            "case {}: accumulated {}, exact {}, allowed {} for {:?}",
+            case,
+            accumulated,
+            exact,
+            allowed,
+            path
+        );
+    }
+}
+
+fn without_cubics(path: &Path) -> Path {
+    Path {
+        commands: path
+            .commands
+            .iter()
+            .map(|command| match *command {
+                PathCommand::CubicTo(p1, _, p) => PathCommand::QuadTo(p1, p),
+                command => command,
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn exact_accumulation_conserves_area() {
+    let mut random = Random::new(9);
+
+    for case in 0..CASES {
+        let path = without_cubics(&random_path(&mut random, 1.0, 255.0));
+        let mut rasterizer = Rasterizer {
+            width: 256,
+            height: 256,
+            accumulation_buffer: vec![0.0; 256 * 256],
+            tolerance: 0.05,
+        };
+
+        rasterizer.draw_path_exact(&path);
+
+        let (accumulated, exact) = (rasterizer.signed_area(), path.signed_area());
+        let (_, magnitude) = flattened_magnitudes(&path, &rasterizer);
+
+        assert!(
+            (accumulated - exact).abs() <= 1e-5 * magnitude,
+            "case {}: accumulated {}, exact {} for {:?}",
+            case,
+            accumulated,
+            exact,
+            path
+        );
+    }
+}
+
+#[test]
+fn exact_coverage_skips_non_finite_curves() {
+    let mut random = Random::new(12);
+
+    for case in 0..CASES / 10 {
+        let [p0, p1, p2] = random.quadratic().points();
+
+        for value in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            for i in 0..6 {
+                let mut points = [p0, p1, p2];
+                let p = &mut points[i / 2];
+
+                if i % 2 == 0 {
+                    p.x = value;
+                } else {
+                    p.y = value;
+                }
+
+                let mut rasterizer = Rasterizer {
+                    width: 64,
+                    height: 64,
+                    accumulation_buffer: vec![0.0; 64 * 64],
+                    tolerance: 0.25,
+                };
+
+                rasterizer
+                    .draw_quadratic_exact(QuadraticBezier::new(points[0], points[1], points[2]));
+
+                // Dropped like a non-finite line
+                assert!(
+                    rasterizer.accumulation_buffer.iter().all(|c| *c == 0.0),
+                    "case {}: {:?}",
+                    case,
+                    points
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn flattening_converges_to_exact_coverage() {
+    let mut random = Random::new(10);
+
+    for case in 0..CASES / 10 {
+        let path = without_cubics(&random_path(&mut random, -20.0, 276.0));
+        let new_rasterizer = || Rasterizer {
+            width: 256,
+            height: 256,
+            accumulation_buffer: vec![0.0; 256 * 256],
+            tolerance: 0.001,
+        };
+        let (mut exact, mut flattened) = (new_rasterizer(), new_rasterizer());
+
+        exact.draw_path_exact(&path);
+        flattened.draw_path(&path, SubdivisionMethod::DeCasteljau);
+
+        let coverage = |rasterizer: &Rasterizer| {
+            rasterizer
+                .accumulation_buffer
+                .chunks(rasterizer.width)
+                .flat_map(|row| {
+                    row.iter().scan(0.0_f32, |acc, cell| {
+                        *acc += cell;
+                        Some(*acc)
+                    })
+                })
+                .collect::<Vec<f32>>()
+        };
+        let difference = coverage(&exact)
+            .iter()
+            .zip(coverage(&flattened).iter())
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0_f32, f32::max);
+
+        assert!(
+            difference <= 0.01,
+            "case {}: winding differs by {} for {:?}",
+            case,
+            difference,
+            path
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn exact_coverage_matches_supersampling() {
+    let mut random = Random::new(11);
+    let (size, samples) = (64, 16);
+
+    for case in 0..CASES / 10 {
+        let path = random_path(&mut random, -8.0, size as f32 + 8.0);
+        let mut rasterizer = Rasterizer {
+            width: size,
+            height: size,
+            accumulation_buffer: vec![0.0; size * size],
+            tolerance: 0.01,
+        };
+
+        rasterizer.draw_path_exact(&path);
+
+        let reference = supersample(&path, size, size, samples);
+        let mut differences = Vec::<f32>::new();
+
+        for (row, expected) in rasterizer
+            .accumulation_buffer
+            .chunks(size)
+            .zip(reference.chunks(size))
+        {
+            let mut acc = 0.0_f32;
+
+            for (cell, expected) in row.iter().zip(expected) {
+                acc += cell;
+                differences.push((acc - expected).abs());
+            }
+        }
+
+        let max = differences.iter().copied().fold(0.0_f32, f32::max);
+        let mean = differences.iter().sum::<f32>() / differences.len() as f32;
+
+        // Point sampling is off by up to about 1 / samples wherever an edge crosses a pixel
+        assert!(
+            max <= 0.1 && mean <= 0.002,
+            "case {}: max difference {}, mean {} for {:?}",
+            case,
+            max,
+            mean,
+            path
+        );
+    }
+}
+
+#[test]
+fn coverage_masks_agree() {
+    let mut random = Random::new(47);
+    let size = 48;
+
+    for case in 0..CASES / 10 {
+        let path = random_path(&mut random, -8.0, size as f32 + 8.0);
+        let mut rasterizer = Rasterizer {
+            width: size,
+            height: size,
+            accumulation_buffer: vec![0.0; size * size],
+            tolerance: 0.25,
+        };
+
+        rasterizer.draw_path(&path, SubdivisionMethod::ParabolaApprox);
+
+        for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let mask = rasterizer.coverage_mask(fill_rule);
+            let mask_u8 = rasterizer.coverage_mask_u8(fill_rule);
+            let mask_u16 = rasterizer.coverage_mask_u16(fill_rule);
+
+            for (y, row) in rasterizer.accumulation_buffer.chunks(size).enumerate() {
+                let mut acc = 0.0_f32;
+
+                for (x, cell) in row.iter().enumerate() {
+                    let i = y * size + x;
+                    acc += cell;
+
+                    // The SIMD prefix sum adds in a different order than the scalar one
+                    let expected = fill_rule.coverage(acc);
+                    assert!(
+                        (mask[i] - expected).abs() <= 1e-5,
+                        "case {}: {:?} mask {} instead of {} at ({}, {})",
+                        case,
+                        fill_rule,
+                        mask[i],
+                        expected,
+                        x,
+                        y
+                    );
+                    assert!(
+                        (mask_u8[i] as f32 - 255.0 * mask[i]).abs() <= 1.0,
+                        "case {}: {:?} 8-bit mask {} for {} at ({}, {})",
+                        case,
+                        fill_rule,
+                        mask_u8[i],
+                        mask[i],
+                        x,
+                        y
+                    );
+                    assert!(
+                        (mask_u16[i] as f32 - 65535.0 * mask[i]).abs() <= 1.0,
+                        "case {}: {:?} 16-bit mask {} for {} at ({}, {})",
+                        case,
+                        fill_rule,
+                        mask_u16[i],
+                        mask[i],
+                        x,
+                        y
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cubics_survive_a_zero_tolerance() {
+    let mut random = Random::new(26);
+
+    for case in 0..CASES {
+        let q = random.quadratic();
+        let [p0, p1, p2] = q.points();
+        // The same curve as a cubic, the error term of the split is 0 up to rounding
+        let (c1, c2) = (p0.lerp(p1, 2.0 / 3.0), p2.lerp(p1, 2.0 / 3.0));
+        let (a, b) = (random.point(0.0, 500.0), random.point(0.0, 500.0));
+        let cases = [
+            ([p0, c1, c2, p2], 0.0),
+            ([p0, a, b, p2], 0.0),
+            ([p0, a, b, p2], -1.0),
+            // 0 / 0 exactly
+            ([p0, p0, p0, p0], 0.0),
+        ];
+
+        for ([p0, p1, p2, p3], tolerance) in cases {
+            let quadratics = cubic_to_quadratics(p0, p1, p2, p3, tolerance);
+
+            assert!(
+                !quadratics.is_empty(),
+                "case {}: cubic dropped at tolerance {}",
+                case,
+                tolerance
+            );
+            assert_close(quadratics[0].eval(0.0), p0, 1e-3, "start");
+            assert_close(quadratics[quadratics.len() - 1].eval(1.0), p3, 1e-3, "end");
+        }
+    }
+}
+
+#[test]
+fn streamed_points_match_subdivide() {
+    let mut random = Random::new(27);
+
+    for case in 0..CASES {
+        let q = random.quadratic();
+        let tolerance = random.range(0.05, 2.0);
+
+        for (name, ts, points) in [
+            (
+                "smart",
+                q.smart_subdivide(tolerance),
+                ParabolaPoints::new(q, tolerance).collect::<Vec<Point>>(),
+            ),
+            (
+                "recursive",
+                q.recursive_subdivide(tolerance),
+                DeCasteljauPoints::new(q, tolerance).collect::<Vec<Point>>(),
+            ),
+        ] {
+            let context = format!("case {}, {} at {}", case, name, tolerance);
+
+            assert_eq!(points.len(), ts.len(), "{}", context);
+
+            for (p, t) in points.iter().zip(ts) {
+                assert_close(*p, q.eval(t), 1e-4, context.as_str());
+            }
+        }
+    }
+}
+
+#[test]
+fn closing_sink_closes_every_subpath() {
+    let mut random = Random::new(28);
+
+    for case in 0..CASES / 10 {
+        let (a, b, c) = (
+            random.point(0.0, 500.0),
+            random.point(0.0, 500.0),
+            random.point(0.0, 500.0),
+        );
+        let mut path = Path::new();
+
+        // Open, closed, lone move and a trailing open subpath
+        path.move_to(a);
+        path.quad_to(b, c);
+        path.move_to(b);
+        path.line_to(c);
+        path.quad_to(a, b.lerp(c, 0.5));
+        path.close();
+        path.move_to(c);
+        path.move_to(a);
+        path.line_to(b);
+
+        let polylines = path.flatten(0.25, SubdivisionMethod::ParabolaApprox);
+        let mut lines = Vec::<(Point, Point)>::new();
+
+        path.flatten_into(
+            0.25,
+            SubdivisionMethod::ParabolaApprox,
+            &mut ClosingSink::new(&mut lines),
+        );
+
+        assert_eq!(polylines.len(), 3, "case {}", case);
+        assert_eq!(
+            polylines.iter().map(|p| p.closed).collect::<Vec<_>>(),
+            [false, true, false],
+            "case {}",
+            case
+        );
+
+        // Every polyline comes back as its segments and one closing line
+        let mut remaining = lines.as_slice();
+
+        for polyline in polylines.iter() {
+            let (subpath, rest) = remaining.split_at(polyline.points.len());
+            let first = polyline.points[0];
+
+            let context = format!("case {}", case);
+
+            for (i, (p0, p1)) in subpath.iter().enumerate() {
+                let next = polyline.points.get(i + 1).copied().unwrap_or(first);
+
+                assert_close(*p0, polyline.points[i], 0.0, context.as_str());
+                assert_close(*p1, next, 0.0, context.as_str());
+            }
+            remaining = rest;
+        }
+
+        assert!(remaining.is_empty(), "case {}", case);
+    }
+}
+
+///
+/// Flattener written outside of the crate, evaluating `smart_subdivide` into a vector
+///
+struct SmartSubdivide;
+
+impl Flattener for SmartSubdivide {
+    type Points = std::vec::IntoIter<Point>;
+
+    fn points(&self, q: QuadraticBezier, tolerance: f32) -> Self::Points {
+        q.smart_subdivide(tolerance)
+            .into_iter()
+            .map(|t| q.eval(t))
+            .collect::<Vec<Point>>()
+            .into_iter()
+    }
+}
+
+#[test]
+fn flatteners_yield_their_subdivide_points() {
+    let mut random = Random::new(29);
+
+    for case in 0..CASES {
+        let q = random.quadratic();
+        let tolerance = random.range(0.05, 2.0);
+        let context = format!("case {} at {}", case, tolerance);
+
+        for (ts, by_method, by_struct) in [
+            (
+                q.smart_subdivide(tolerance),
+                q.flatten(tolerance, SubdivisionMethod::ParabolaApprox)
+                    .collect::<Vec<Point>>(),
+                q.flatten(tolerance, ParabolaApprox).collect::<Vec<Point>>(),
+            ),
+            (
+                q.recursive_subdivide(tolerance),
+                q.flatten(tolerance, SubdivisionMethod::DeCasteljau)
+                    .collect::<Vec<Point>>(),
+                q.flatten(tolerance, &DeCasteljau).collect::<Vec<Point>>(),
+            ),
+        ] {
+            assert_eq!(by_method.len(), ts.len(), "{}", context);
+            assert_eq!(by_struct.len(), ts.len(), "{}", context);
+
+            for ((a, b), t) in by_method.iter().zip(&by_struct).zip(ts) {
+                assert_close(*a, q.eval(t), 1e-4, context.as_str());
+                assert_close(*b, *a, 0.0, context.as_str());
+            }
+        }
+    }
+}
+
+#[test]
+fn rasterizer_accepts_any_flattener() {
+    let mut random = Random::new(30);
+
+    for case in 0..CASES / 10 {
+        let (path, _) = random_ellipse(&mut random);
+        let rasterize = |flattener: &dyn Fn(&mut Rasterizer)| {
+            let mut rasterizer = Rasterizer {
+                width: 256,
+                height: 256,
+                accumulation_buffer: vec![0.0; 256 * 256],
+                tolerance: 0.25,
+            };
+
+            flattener(&mut rasterizer);
+            rasterizer.accumulation_buffer
+        };
+        let built_in = rasterize(&|r| r.draw_path(&path, SubdivisionMethod::ParabolaApprox));
+        let plugged = rasterize(&|r| r.draw_path(&path, SmartSubdivide));
+
+        assert!(
+            built_in
+                .iter()
+                .zip(&plugged)
+                .all(|(a, b)| (a - b).abs() <= 1e-5),
+            "case {}",
+            case
+        );
+    }
+}
+
+#[test]
+fn baselines_sample_evenly_spaced_parameters() {
+    let mut random = Random::new(31);
+
+    for case in 0..CASES {
+        let q = random.quadratic();
+        let tolerance = random.range(0.05, 2.0);
+        let [p0, p1, p2] = q.points();
+        let context = format!("case {} at {}", case, tolerance);
+        let evenly_spaced = |points: &[Point], epsilon: f32, context: &str| {
+            let n = points.len() - 1;
+
+            for (i, p) in points.iter().enumerate() {
+                assert_close(*p, q.eval(i as f32 / n as f32), epsilon, context);
+            }
+        };
+
+        for segments in [0, 1, 7] {
+            let points = q
+                .flatten(tolerance, SubdivisionMethod::Uniform { segments })
+                .collect::<Vec<Point>>();
+
+            assert_eq!(points.len(), segments.max(1) + 1, "{}", context);
+            evenly_spaced(points.as_slice(), 1e-3, context.as_str());
+        }
+
+        // Wang's formula on the second difference
+        let wang = q
+            .flatten(tolerance, SubdivisionMethod::Wang)
+            .collect::<Vec<Point>>();
+        let dd = f32::hypot(p0.x - 2.0 * p1.x + p2.x, p0.y - 2.0 * p1.y + p2.y);
+        let count = (dd / (4.0 * tolerance)).sqrt().ceil().max(1.0) as usize;
+
+        assert_eq!(wang.len(), count + 1, "{}", context);
+        evenly_spaced(wang.as_slice(), 1e-3, context.as_str());
+
+        // Forward differencing halves its step, and lands on the end point exactly
+        let forward = q
+            .flatten(tolerance, SubdivisionMethod::ForwardDifferencing)
+            .collect::<Vec<Point>>();
+
+        assert!((forward.len() - 1).is_power_of_two(), "{}", context);
+        assert_close(forward[forward.len() - 1], p2, 0.0, context.as_str());
+        // The additions drift a little away from the curve over long walks
+        evenly_spaced(forward.as_slice(), 1e-2, context.as_str());
+    }
+}
+
+#[test]
+fn zero_tolerances_are_capped() {
+    let mut random = Random::new(32);
+
+    for case in 0..CASES / 50 {
+        let q = random.quadratic();
+        let [p0, p1, p2] = q.points();
+
+        for tolerance in [0.0, -1.0, 1e-30] {
+            let context = format!("case {} at {}", case, tolerance);
+
+            for method in [
+                SubdivisionMethod::ParabolaApprox,
+                SubdivisionMethod::DeCasteljau,
+                SubdivisionMethod::Wang,
+                SubdivisionMethod::ForwardDifferencing,
+                SubdivisionMethod::Uniform {
+                    segments: usize::MAX,
+                },
+            ] {
+                let count = q.flatten(tolerance, method).count();
+
+                assert!(
+                    (2..=MAX_SEGMENTS + 1).contains(&count),
+                    "{} with {:?}: {} points",
+                    context,
+                    method,
+                    count
+                );
+            }
+
+            assert!(q.smart_subdivide(tolerance).len() <= MAX_SEGMENTS + 1);
+            assert!(q.recursive_subdivide(tolerance).len() <= MAX_SEGMENTS + 1);
+            assert!(cubic_to_quadratics(p0, p2, p1, p2, tolerance).len() <= MAX_SEGMENTS);
+
+            #[cfg(feature = "std")]
+            {
+                let mut batch = QuadraticBatch::new();
+                let (mut points, mut offsets) = (Vec::new(), Vec::new());
+
+                batch.push(q);
+                batch.push(q);
+                batch.flatten::<4>(tolerance, &mut points, &mut offsets);
+
+                assert_eq!(points.len(), 2 * q.smart_subdivide(tolerance).len());
+            }
+        }
+    }
+}
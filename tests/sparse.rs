@@ -0,0 +1,152 @@
+//!
+//! Checks that the sparse rasterizer resolves the same coverage and render as the dense one,
+//! on canvases whose last tiles are partial and with shapes leaving the canvas.
+//!
+
+#![cfg(feature = "std")]
+
+use flattening_quadratic_bezier_curves::path::Path;
+use flattening_quadratic_bezier_curves::rasterizer::FillRule;
+use flattening_quadratic_bezier_curves::sparse::{SparseRasterizer, TILE_SIZE};
+use flattening_quadratic_bezier_curves::{Color, Rasterizer, SubdivisionMethod};
+
+///
+/// Sizes that are a multiple of the tile size, smaller than a tile and in between
+///
+const SIZES: [(usize, usize); 4] = [(64, 32), (37, 21), (5, 9), (16, 40)];
+
+///
+/// Overlapping and self intersecting shapes, all scaled to the canvas so they reach its
+/// right and bottom edges, plus one crossing every border
+///
+fn paths(width: usize, height: usize) -> Vec<Path> {
+    let (w, h) = (width as f32, height as f32);
+    let svg = [
+        format!(
+            "M{} {} A{} {} 0 1 0 {} {} A{} {} 0 1 0 {} {} Z",
+            0.1 * w,
+            0.5 * h,
+            0.45 * w,
+            0.45 * h,
+            0.99 * w,
+            0.5 * h,
+            0.45 * w,
+            0.45 * h,
+            0.1 * w,
+            0.5 * h
+        ),
+        format!(
+            "M{} {} L{} {} L{} {} L{} {} L{} {} Z",
+            0.5 * w,
+            0.02 * h,
+            0.8 * w,
+            h,
+            0.03 * w,
+            0.35 * h,
+            w,
+            0.35 * h,
+            0.2 * w,
+            h
+        ),
+        format!(
+            "M-3 -2.5 Q{} {} {} {} L{} -4 Z M{} {} H{} V{} H{} Z",
+            0.5 * w,
+            2.0 * h,
+            w + 5.0,
+            0.6 * h,
+            0.7 * w,
+            0.25 * w + 0.3,
+            0.25 * h + 0.7,
+            0.75 * w + 0.2,
+            0.75 * h + 0.4,
+            0.25 * w + 0.3
+        ),
+    ];
+
+    svg.iter().map(|d| Path::from_svg(d).unwrap()).collect()
+}
+
+fn rasterizers(width: usize, height: usize) -> (Rasterizer, SparseRasterizer) {
+    let mut dense = Rasterizer {
+        width,
+        height,
+        accumulation_buffer: vec![0.0; width * height],
+        tolerance: 0.1,
+    };
+    let mut sparse = SparseRasterizer::new(width, height, 0.1);
+
+    for path in paths(width, height) {
+        dense.draw_path(&path, SubdivisionMethod::ParabolaApprox);
+        sparse.draw_path(&path, SubdivisionMethod::ParabolaApprox);
+    }
+
+    (dense, sparse)
+}
+
+#[test]
+fn sparse_coverage_matches_dense() {
+    for (width, height) in SIZES {
+        let (dense, sparse) = rasterizers(width, height);
+
+        assert!(sparse.tile_count() > 0);
+
+        for fill_rule in [FillRule::NonZero, FillRule::EvenOdd] {
+            let expected = dense.coverage_mask(fill_rule);
+            let coverage = sparse.coverage(fill_rule);
+            // Pixels not reached by a strip or a span are uncovered
+            let mut actual = vec![0.0_f32; width * height];
+
+            for span in coverage.spans.iter() {
+                actual[span.y * width + span.x0..span.y * width + span.x1].fill(span.coverage);
+            }
+
+            for strip in coverage.strips.iter() {
+                for (i, value) in strip.coverage.iter().enumerate() {
+                    let (x, y) = (strip.x + i % TILE_SIZE, strip.y + i / TILE_SIZE);
+
+                    if x < width && y < height {
+                        actual[y * width + x] = *value;
+                    } else {
+                        assert_eq!(*value, 0.0, "strip past the canvas at {}, {}", x, y);
+                    }
+                }
+            }
+
+            for (i, (a, e)) in actual.iter().zip(&expected).enumerate() {
+                assert!(
+                    (a - e).abs() <= 1e-5,
+                    "{}x{} {:?} pixel ({}, {}): {} instead of {}",
+                    width,
+                    height,
+                    fill_rule,
+                    i % width,
+                    i / width,
+                    a,
+                    e
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn sparse_render_matches_dense() {
+    let (fg, bg) = (Color::yellow_green(), Color::steel_blue());
+
+    for (width, height) in SIZES {
+        let (dense, sparse) = rasterizers(width, height);
+        let expected = dense.render(fg, bg);
+        let actual = sparse.render(fg, bg, FillRule::NonZero);
+
+        assert_eq!(actual.len(), expected.len());
+        assert!(
+            actual
+                .iter()
+                .zip(&expected)
+                .all(|(a, e)| (a - e).abs() <= 1e-5),
+            "{}x{}",
+            width,
+            height
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//!
+//! Checks of the SVG subset through `render_svg`: inheritance of the presentation attributes,
+//! the geometry of the basic shapes and the stroke joins and caps.
+//!
+
+#![cfg(feature = "std")]
+
+mod common;
+
+use std::f32::consts::PI;
+
+use flattening_quadratic_bezier_curves::canvas::Canvas;
+use flattening_quadratic_bezier_curves::svg::render_svg;
+use flattening_quadratic_bezier_curves::SubdivisionMethod;
+
+use common::{assert_pixel, pixel};
+
+fn render(body: &str, width: usize, height: usize) -> Canvas {
+    let source = format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">{}</svg>"#,
+        width, height, body
+    );
+
+    render_svg(source.as_str(), 0.05, SubdivisionMethod::ParabolaApprox).unwrap()
+}
+
+fn coverage(canvas: &Canvas) -> f32 {
+    canvas.pixels.chunks(4).map(|p| p[3]).sum()
+}
+
+fn assert_coverage(canvas: &Canvas, expected: f32, tolerance: f32, context: &str) {
+    let actual = coverage(canvas);
+
+    assert!(
+        (actual - expected).abs() <= tolerance,
+        "{}: coverage {} instead of {}",
+        context,
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn attributes_inherit_from_groups() {
+    let canvas = render(
+        r##"<g fill="#ff0000" fill-opacity="0.5">
+              <rect width="10" height="10"/>
+              <rect x="10" width="10" height="10" fill="#0000ff"/>
+              <g color="#00ff00" transform="translate(20, 0)">
+                <rect width="10" height="10" fill="currentColor" style="fill-opacity: 1"/>
+              </g>
+            </g>
+            <g opacity="0.5"><rect x="30" width="10" height="10" opacity="0.5"/></g>
+            <g fill="none"><rect x="40" width="10" height="10"/></g>"##,
+        50,
+        10,
+    );
+
+    assert_pixel(pixel(&canvas, 5, 5), [0.5, 0.0, 0.0, 0.5], "group fill");
+    assert_pixel(pixel(&canvas, 15, 5), [0.0, 0.0, 0.5, 0.5], "own fill");
+    assert_pixel(pixel(&canvas, 25, 5), [0.0, 1.0, 0.0, 1.0], "currentColor");
+    // Group and element opacity multiply
+    assert_pixel(pixel(&canvas, 35, 5), [0.0, 0.0, 0.0, 0.25], "opacity");
+    assert_pixel(pixel(&canvas, 45, 5), [0.0, 0.0, 0.0, 0.0], "no fill");
+}
+
+#[test]
+fn viewbox_scales_the_content() {
+    let canvas = render_svg(
+        r#"<svg width="40" height="20" viewBox="0 0 20 10"><rect width="5" height="5"/></svg>"#,
+        0.05,
+        SubdivisionMethod::ParabolaApprox,
+    )
+    .unwrap();
+
+    assert_eq!((canvas.width, canvas.height), (40, 20));
+    assert_coverage(&canvas, 100.0, 1e-2, "viewBox");
+}
+
+#[test]
+fn shapes_cover_their_area() {
+    let cases = [
+        (r#"<rect x="2.5" y="3.5" width="10" height="6"/>"#, 60.0),
+        (
+            r#"<rect x="2" y="2" width="30" height="20" rx="5"/>"#,
+            600.0 - (4.0 - PI) * 25.0,
+        ),
+        (
+            r#"<rect x="2" y="2" width="30" height="20" rx="40" ry="4"/>"#,
+            600.0 - (4.0 - PI) * 15.0 * 4.0,
+        ),
+        (r#"<circle cx="20" cy="15" r="8"/>"#, PI * 64.0),
+        (r#"<ellipse cx="20" cy="15" rx="12" ry="5"/>"#, PI * 60.0),
+        (r#"<polygon points="2,2 32,2 2,22"/>"#, 300.0),
+        // Filling closes polylines implicitly
+        (r#"<polyline points="2,2 32,2 2,22"/>"#, 300.0),
+        // Both subpaths wind the same way
+        (
+            r#"<path d="M2 2 H32 V22 H2 Z M10 10 h10 v5 h-10 Z"/>"#,
+            600.0,
+        ),
+        (
+            r#"<path fill-rule="evenodd" d="M2 2 H32 V22 H2 Z M10 10 h10 v5 h-10 Z"/>"#,
+            550.0,
+        ),
+        (r#"<line x1="2" y1="2" x2="30" y2="20"/>"#, 0.0),
+        (r#"<rect width="0" height="10"/>"#, 0.0),
+        (r#"<circle cx="10" cy="10"/>"#, 0.0),
+    ];
+
+    for (shape, area) in cases {
+        let canvas = render(shape, 40, 30);
+
+        assert_coverage(&canvas, area, 0.01 * area + 1e-3, shape);
+    }
+}
+
+fn stroke(attributes: &str) -> Canvas {
+    render(
+        format!(
+            r#"<polyline points="10,30 30,10 50,30" fill="none" stroke="black" stroke-width="6" {}/>"#,
+            attributes
+        )
+        .as_str(),
+        60,
+        40,
+    )
+}
+
+#[test]
+fn stroke_joins() {
+    let miter = stroke(r#"stroke-linejoin="miter""#);
+    let round = stroke(r#"stroke-linejoin="round""#);
+    let bevel = stroke(r#"stroke-linejoin="bevel""#);
+    let limited = stroke(r#"stroke-linejoin="miter" stroke-miterlimit="1""#);
+    // The outer corner of the right angle: a square for the miter, a quarter disk for the
+    // round join and a triangle for the bevel, all on top of the bevelled stroke. Round
+    // shapes get more slack: where the disk overlaps the antialiased edges of the segments,
+    // the coverage of both adds up in the same pixels.
+    let half = 3.0_f32;
+    let bevel_area = coverage(&bevel);
+
+    assert_coverage(&miter, bevel_area + 0.5 * half * half, 0.3, "miter");
+    assert_coverage(
+        &round,
+        bevel_area + (0.25 * PI - 0.5) * half * half,
+        1.0,
+        "round",
+    );
+    // Past the limit a miter falls back to a bevel
+    assert_coverage(&limited, bevel_area, 1e-3, "miter limit");
+
+    // The miter tip reaches 3√2 above the vertex, the others stop before y = 7
+    assert!(pixel(&miter, 30, 6)[3] > 0.5);
+    assert!(pixel(&round, 30, 6)[3] < 1e-3);
+    assert!(pixel(&bevel, 30, 6)[3] < 1e-3);
+}
+
+#[test]
+fn stroke_caps() {
+    let line = |cap: &str| {
+        render(
+            format!(
+                r#"<line x1="10" y1="10" x2="30" y2="10" stroke="black" stroke-width="4" stroke-linecap="{}"/>"#,
+                cap
+            )
+            .as_str(),
+            40,
+            20,
+        )
+    };
+
+    assert_coverage(&line("butt"), 80.0, 0.1, "butt");
+    assert_coverage(&line("square"), 96.0, 0.1, "square");
+    assert_coverage(&line("round"), 80.0 + 4.0 * PI, 0.5, "round");
+}
+
+#[test]
+fn invalid_documents_are_errors() {
+    let render = |source: &str| render_svg(source, 0.25, SubdivisionMethod::ParabolaApprox);
+
+    assert!(render("<g/>").is_err());
+    assert!(render(r##"<svg width="10" height="10"><rect fill="#12"/></svg>"##).is_err());
+    assert!(render(r#"<svg viewBox="0 0 -1 10"/>"#).is_err());
+}
+
+#[test]
+fn tolerances_must_be_positive() {
+    let source = r#"<svg width="40" height="40"><polyline points="5,30 20,5 35,30" fill="none" stroke="black" stroke-width="6" stroke-linejoin="round" stroke-linecap="round"/></svg>"#;
+
+    for tolerance in [0.0, -0.25, f32::NAN, f32::INFINITY] {
+        for method in [
+            SubdivisionMethod::ParabolaApprox,
+            SubdivisionMethod::DeCasteljau,
+        ] {
+            assert!(
+                render_svg(source, tolerance, method).is_err(),
+                "{} with {:?}",
+                tolerance,
+                method
+            );
+        }
+    }
+
+    // Round joins and caps far below a pixel stay bounded
+    let canvas = render_svg(source, 1e-9, SubdivisionMethod::DeCasteljau).unwrap();
+
+    assert!(coverage(&canvas) > 0.0);
+}